@@ -6,6 +6,11 @@ pub fn get_handler() -> impl Fn(Invoke) -> bool {
         auth::save_auth_token,
         auth::delete_auth_token,
         auth::get_auth_token,
+        auth::accounts::list_accounts,
+        auth::accounts::save_account,
+        auth::accounts::delete_account,
+        auth::accounts::set_default_account,
+        auth::accounts::get_account_token,
         repo::check_is_git_repo,
         repo::get_last_opened_repo,
         repo::get_repo_state,
@@ -13,22 +18,86 @@ pub fn get_handler() -> impl Fn(Invoke) -> bool {
         repo::remove_repo_from_view,
         repo::clone::clone_repo,
         repo::status::get_repo_changes,
+        repo::status::get_repo_status,
         repo::file::get_file_diff,
         repo::branch::list_branches,
         repo::commit::get_commits,
         repo::file::get_file_diff_by_commit,
+        repo::diff::get_working_diff,
+        repo::diff::get_commit_diff,
         repo::status::get_repo_changes_from_commit,
         repo::staging::stage_file,
         repo::staging::stage_all_files,
         repo::staging::unstage_file,
         repo::staging::unstage_all_files,
+        repo::staging::get_file_hunks,
+        repo::staging::stage_hunks,
+        repo::staging::unstage_hunks,
+        repo::discard::discard_file_changes,
+        repo::discard::discard_hunks,
+        repo::discard::discard_all_changes,
+        repo::stash::save_stash,
+        repo::stash::list_stashes,
+        repo::stash::get_stash_changes,
+        repo::stash::get_stash_file_diff,
+        repo::stash::apply_stash_entry,
+        repo::stash::pop_stash,
+        repo::stash::drop_stash,
         repo::commit::commit,
+        repo::commit::amend_commit,
+        repo::sequencer::cherry_pick_commits,
+        repo::sequencer::revert_commits,
+        repo::sequencer::continue_sequence,
+        repo::sequencer::abort_sequence,
+        repo::reset::reset_to_commit,
+        repo::reset::undo_last_reset,
+        repo::rebase::get_rebase_plan,
+        repo::rebase::start_interactive_rebase,
+        repo::rebase::get_rebase_progress,
+        repo::rebase::continue_rebase,
+        repo::rebase::skip_rebase_step,
+        repo::rebase::abort_rebase,
         repo::branch::create_branch,
         repo::branch::checkout_branch,
+        repo::branch::checkout_remote_branch,
+        repo::branch::checkout_detached,
+        repo::checkout::get_checkout_blockers,
+        repo::branch::delete_branch,
+        repo::branch::delete_remote_branch,
+        repo::branch::rename_branch,
+        repo::branch::set_branch_upstream,
         repo::branch::merge_analysis,
         repo::branch::fast_forward,
         repo::branch::normal_merge,
+        repo::merge::get_merge_state,
+        repo::merge::list_conflicts,
+        repo::merge::get_conflict_versions,
+        repo::merge::write_conflict_resolution,
+        repo::merge::resolve_conflict_with_side,
+        repo::merge::mark_conflict_resolved,
+        repo::merge::conclude_merge,
+        repo::merge::abort_merge,
         repo::remote::fetch_repo,
-        repo::remote::push_repo
+        repo::remote::fetch_all_remotes,
+        repo::remote::list_remotes,
+        repo::remote::add_remote,
+        repo::remote::rename_remote,
+        repo::remote::remove_remote,
+        repo::remote::edit_remote,
+        repo::pull::pull_repo,
+        repo::remote::push_repo,
+        repo::remote::delete_remote_refs,
+        repo::auto_fetch::get_auto_fetch_settings,
+        repo::auto_fetch::set_auto_fetch_settings,
+        repo::auto_fetch::trigger_auto_fetch,
+        repo::operation::cancel_operation,
+        repo::operation::list_operations,
+        repo::ssh::get_remote_credential_method,
+        repo::ssh::set_remote_credential_method,
+        repo::ssh::list_ssh_keys,
+        repo::ssh::get_ssh_prompts,
+        repo::ssh::set_ssh_key_passphrase,
+        repo::ssh::forget_ssh_key_passphrase,
+        repo::ssh::trust_ssh_host
     ]
 }
@@ -0,0 +1,345 @@
+use keyring::Entry;
+use parking_lot::{const_mutex, Mutex};
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+use crate::auth::error::{Error, Result};
+use crate::auth::get_auth_token;
+use crate::config::config;
+use crate::repo::remote::remote_endpoint;
+
+const DEFAULT_HOST: &str = "github.com";
+
+/// One signed-in account. The token lives in its own keyring entry; the
+/// list of accounts is kept as JSON under `ACCOUNTS_KEY`, since the keyring
+/// can't enumerate entries.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Account {
+    host: String,
+    login: String,
+    is_default: bool,
+}
+
+/// Serialises read-modify-write of the account list.
+static ACCOUNTS_LOCK: Mutex<()> = const_mutex(());
+
+/// Accepts `ghe.example.com` as well as a pasted `https://ghe.example.com/`.
+/// The port is dropped since remotes are matched by host alone.
+fn normalize_host(host: &str) -> Result<String> {
+    let host = host.trim();
+    let host = host.split_once("://").map_or(host, |(_, rest)| rest);
+    let host = host.split('/').next().unwrap_or_default();
+    let host = host.rsplit('@').next().unwrap_or_default();
+    // Bracketed IPv6 hosts contain colons of their own.
+    let port_start = host.rfind(']').unwrap_or(0);
+    let host = match host[port_start..].rfind(':') {
+        Some(idx) => &host[..port_start + idx],
+        None => host,
+    }
+    .to_lowercase();
+
+    if host.is_empty() || host.contains(char::is_whitespace) {
+        return Err(Error::AccountError(format!(
+            "\"{}\" is not a valid host",
+            host
+        )));
+    }
+    Ok(host)
+}
+
+fn normalize_login(login: &str) -> Result<String> {
+    let login = login.trim();
+    if login.is_empty() {
+        return Err(Error::AccountError("Login cannot be empty".to_string()));
+    }
+    Ok(login.to_string())
+}
+
+fn token_entry(host: &str, login: &str) -> Result<Entry> {
+    Ok(Entry::new(
+        config().SERVICE_NAME,
+        &format!("{}@{}", login, host),
+    )?)
+}
+
+fn read_accounts() -> Result<Vec<Account>> {
+    let entry = Entry::new(config().SERVICE_NAME, config().ACCOUNTS_KEY)?;
+    match entry.get_password() {
+        Ok(json) => Ok(serde_json::from_str(&json)?),
+        Err(keyring::Error::NoEntry) => Ok(Vec::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn write_accounts(accounts: &[Account]) -> Result<()> {
+    let entry = Entry::new(config().SERVICE_NAME, config().ACCOUNTS_KEY)?;
+    if accounts.is_empty() {
+        return match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(err) => Err(err.into()),
+        };
+    }
+
+    entry.set_password(&serde_json::to_string(accounts)?)?;
+    Ok(())
+}
+
+/// Keeps exactly one default per host, preferring the current one.
+fn ensure_default(accounts: &mut [Account], host: &str) {
+    if accounts
+        .iter()
+        .any(|account| account.host == host && account.is_default)
+    {
+        return;
+    }
+    if let Some(account) = accounts.iter_mut().find(|account| account.host == host) {
+        account.is_default = true;
+    }
+}
+
+/// The account to use for `host`: the one named by `login` if given,
+/// otherwise the host's default.
+fn find_account<'a>(
+    accounts: &'a [Account],
+    host: &str,
+    login: Option<&str>,
+) -> Option<&'a Account> {
+    let mut candidates = accounts.iter().filter(|account| account.host == host);
+    match login {
+        Some(login) => candidates.find(|account| account.login.eq_ignore_ascii_case(login)),
+        None => candidates.find(|account| account.is_default),
+    }
+}
+
+/// `user` in `https://user@host/...`, which picks the account when a host
+/// has several.
+fn url_login(url: &str) -> Option<&str> {
+    let (_, rest) = url.split_once("://")?;
+    let authority = rest.split('/').next()?;
+    let (userinfo, _) = authority.rsplit_once('@')?;
+    let login = userinfo.split(':').next()?;
+    (!login.is_empty()).then_some(login)
+}
+
+/// Tokens are only ever sent over HTTPS.
+fn is_https(url: &str) -> bool {
+    url.get(..8)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("https://"))
+}
+
+/// Whether `url` points at the host the sign-in session token was issued
+/// for. That token must not go anywhere else.
+pub(crate) fn is_session_host(url: &str) -> bool {
+    is_https(url)
+        && remote_endpoint(url).is_some_and(|(host, _)| host.eq_ignore_ascii_case(DEFAULT_HOST))
+}
+
+/// Username and token for a remote URL, from the account saved for its
+/// host. github.com falls back to the session token from signing in.
+pub(crate) fn account_credentials(url: &str) -> Option<(String, String)> {
+    if !is_https(url) {
+        return None;
+    }
+    let (host, _) = remote_endpoint(url)?;
+    let host = host.to_lowercase();
+
+    let account = {
+        let _lock = ACCOUNTS_LOCK.lock();
+        let accounts = read_accounts().ok()?;
+        url_login(url)
+            .and_then(|login| find_account(&accounts, &host, Some(login)))
+            .or_else(|| find_account(&accounts, &host, None))
+            .cloned()
+    };
+
+    match account {
+        Some(account) => {
+            let token = token_entry(&account.host, &account.login)
+                .and_then(|entry| Ok(entry.get_password()?))
+                .ok()?;
+            Some((account.login, token))
+        }
+        None if is_session_host(url) => get_auth_token()
+            .ok()
+            .map(|token| ("x-access-token".to_string(), token)),
+        None => None,
+    }
+}
+
+#[command]
+pub fn list_accounts(host: Option<String>) -> Result<Vec<Account>> {
+    let host = host.map(|value| normalize_host(&value)).transpose()?;
+    let _lock = ACCOUNTS_LOCK.lock();
+
+    Ok(read_accounts()?
+        .into_iter()
+        .filter(|account| host.as_ref().map_or(true, |host| account.host == *host))
+        .collect())
+}
+
+/// Adds an account or replaces its token. The first account for a host
+/// becomes its default.
+#[command]
+pub fn save_account(
+    host: String,
+    login: String,
+    token: String,
+    make_default: Option<bool>,
+) -> Result<Vec<Account>> {
+    let host = normalize_host(&host)?;
+    let login = normalize_login(&login)?;
+    if token.trim().is_empty() {
+        return Err(Error::AccountError("Token cannot be empty".to_string()));
+    }
+
+    let _lock = ACCOUNTS_LOCK.lock();
+    let mut accounts = read_accounts()?;
+
+    // Logins are case-insensitive; keep the spelling the entry was saved with.
+    let existing =
+        find_account(&accounts, &host, Some(&login)).map(|account| account.login.clone());
+    let login = existing.clone().unwrap_or(login);
+    token_entry(&host, &login)?.set_password(token.trim())?;
+
+    if existing.is_none() {
+        accounts.push(Account {
+            host: host.clone(),
+            login: login.clone(),
+            is_default: false,
+        });
+    }
+    if make_default.unwrap_or(false) {
+        for account in accounts.iter_mut().filter(|account| account.host == host) {
+            account.is_default = account.login.eq_ignore_ascii_case(&login);
+        }
+    }
+    ensure_default(&mut accounts, &host);
+    write_accounts(&accounts)?;
+
+    Ok(accounts)
+}
+
+#[command]
+pub fn delete_account(host: String, login: String) -> Result<Vec<Account>> {
+    let host = normalize_host(&host)?;
+    let login = normalize_login(&login)?;
+
+    let _lock = ACCOUNTS_LOCK.lock();
+    let mut accounts = read_accounts()?;
+    let login = match find_account(&accounts, &host, Some(&login)) {
+        Some(account) => account.login.clone(),
+        None => {
+            return Err(Error::AccountError(format!(
+                "No account {} for {}",
+                login, host
+            )))
+        }
+    };
+
+    match token_entry(&host, &login)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => {}
+        Err(err) => return Err(err.into()),
+    }
+    accounts.retain(|account| !(account.host == host && account.login == login));
+    ensure_default(&mut accounts, &host);
+    write_accounts(&accounts)?;
+
+    Ok(accounts)
+}
+
+#[command]
+pub fn set_default_account(host: String, login: String) -> Result<Vec<Account>> {
+    let host = normalize_host(&host)?;
+    let login = normalize_login(&login)?;
+
+    let _lock = ACCOUNTS_LOCK.lock();
+    let mut accounts = read_accounts()?;
+    if find_account(&accounts, &host, Some(&login)).is_none() {
+        return Err(Error::AccountError(format!(
+            "No account {} for {}",
+            login, host
+        )));
+    }
+
+    for account in accounts.iter_mut().filter(|account| account.host == host) {
+        account.is_default = account.login.eq_ignore_ascii_case(&login);
+    }
+    write_accounts(&accounts)?;
+
+    Ok(accounts)
+}
+
+/// Token of `login` on `host`, or of the host's default account.
+#[command]
+pub fn get_account_token(host: String, login: Option<String>) -> Result<String> {
+    let host = normalize_host(&host)?;
+
+    let account = {
+        let _lock = ACCOUNTS_LOCK.lock();
+        let accounts = read_accounts()?;
+        find_account(&accounts, &host, login.as_deref().map(str::trim))
+            .cloned()
+            .ok_or_else(|| Error::AccountError(format!("No account saved for {}", host)))?
+    };
+
+    Ok(token_entry(&account.host, &account.login)?.get_password()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalized_hosts_match_remote_hosts() {
+        for (input, url) in [
+            ("GHE.example.com", "https://ghe.example.com/owner/repo.git"),
+            (
+                "https://ghe.example.com:8443/",
+                "https://ghe.example.com:8443/owner/repo.git",
+            ),
+            ("ghe.example.com:8443", "git@ghe.example.com:owner/repo.git"),
+            ("[::1]:8443", "https://[::1]:8443/owner/repo.git"),
+        ] {
+            let (remote_host, _) = remote_endpoint(url).unwrap();
+            assert_eq!(normalize_host(input).unwrap(), remote_host.to_lowercase());
+        }
+        assert!(normalize_host(" ").is_err());
+    }
+
+    #[test]
+    fn picks_the_login_then_the_default() {
+        let account = |login: &str, is_default| Account {
+            host: "ghe.example.com".to_string(),
+            login: login.to_string(),
+            is_default,
+        };
+        let mut accounts = vec![account("alice", false), account("bob", false)];
+        assert!(find_account(&accounts, "ghe.example.com", None).is_none());
+
+        ensure_default(&mut accounts, "ghe.example.com");
+        assert_eq!(
+            find_account(&accounts, "ghe.example.com", None)
+                .unwrap()
+                .login,
+            "alice"
+        );
+        assert_eq!(
+            find_account(&accounts, "ghe.example.com", Some("BOB"))
+                .unwrap()
+                .login,
+            "bob"
+        );
+        assert_eq!(
+            url_login("https://bob@ghe.example.com/owner/repo.git"),
+            Some("bob")
+        );
+        assert_eq!(url_login("git@ghe.example.com:owner/repo.git"), None);
+    }
+
+    #[test]
+    fn sends_tokens_only_over_https() {
+        assert!(is_session_host("https://github.com/owner/repo.git"));
+        assert!(!is_session_host("http://github.com/owner/repo.git"));
+        assert!(account_credentials("http://ghe.example.com/owner/repo.git").is_none());
+    }
+}
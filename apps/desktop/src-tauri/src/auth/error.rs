@@ -5,6 +5,8 @@ pub type Result<T> = core::result::Result<T, Error>;
 #[derive(Debug)]
 pub enum Error {
     KeyringError(keyring::Error),
+    SerdeJson(serde_json::Error),
+    AccountError(String),
 }
 
 impl Serialize for Error {
@@ -20,6 +22,7 @@ impl Serialize for Error {
 
 crate::impl_froms! {
     KeyringError(keyring::Error),
+    SerdeJson(serde_json::Error),
 }
 
 // endregion: --- Froms
@@ -27,7 +30,11 @@ crate::impl_froms! {
 // region:    --- Error Boilerplate
 impl core::fmt::Display for Error {
     fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::result::Result<(), core::fmt::Error> {
-        write!(fmt, "{self:?}")
+        match self {
+            Self::KeyringError(e) => write!(fmt, "{e}"),
+            Self::SerdeJson(e) => write!(fmt, "{e}"),
+            Self::AccountError(e) => write!(fmt, "{e}"),
+        }
     }
 }
 
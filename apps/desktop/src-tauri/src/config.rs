@@ -24,9 +24,11 @@ pub struct Config {
     // -- Web
     pub SERVICE_NAME: &'static str,
     pub USER_KEY: &'static str,
+    pub ACCOUNTS_KEY: &'static str,
     pub STORE_NAME: &'static str,
     pub STORE_LAST_OPENED_REPOS_KEY: &'static str,
     pub STORE_REPOS_KEY: &'static str,
+    pub STORE_AUTO_FETCH_KEY: &'static str,
 }
 
 impl Config {
@@ -34,9 +36,11 @@ impl Config {
         Ok(Config {
             SERVICE_NAME: "better-github-desktop",
             USER_KEY: "session-token",
+            ACCOUNTS_KEY: "accounts",
             STORE_NAME: "settings.json",
             STORE_LAST_OPENED_REPOS_KEY: "last_opened_repo",
             STORE_REPOS_KEY: "repos",
+            STORE_AUTO_FETCH_KEY: "auto_fetch",
         })
     }
 }
@@ -41,6 +41,10 @@ pub fn run() {
                 app.deep_link().register_all()?;
             }
 
+            if let Err(err) = repo::auto_fetch::start_auto_fetch(app.handle()) {
+                log::warn!("Failed to start auto-fetch: {}", err);
+            }
+
             Ok(())
         })
         .run(tauri::generate_context!())
@@ -0,0 +1,460 @@
+use std::{
+    collections::HashMap,
+    thread,
+    time::{Duration, Instant},
+};
+
+use git2::{ErrorClass, Oid, Repository};
+use parking_lot::{const_mutex, Condvar, Mutex};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{command, AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+
+use crate::{
+    config::config,
+    repo::{
+        branch::upstream_of,
+        error::{Error, Result},
+        remote::{fetch_remote, FetchRequestOptions},
+    },
+    utils::store_helper::{get_last_opened_repo_path, get_repo_paths},
+};
+
+const DEFAULT_INTERVAL_SECS: u64 = 300;
+const MIN_INTERVAL_SECS: u64 = 60;
+const MAX_BACKOFF_SECS: u64 = 3600;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoFetchSettings {
+    enabled: bool,
+    interval_secs: u64,
+    /// Fetch every repository in the store instead of only the active one.
+    all_repos: bool,
+    pause_on_battery: bool,
+}
+
+impl Default for AutoFetchSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: DEFAULT_INTERVAL_SECS,
+            all_repos: false,
+            pause_on_battery: true,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct AutoFetchStatusPayload {
+    repo_path: Option<String>,
+    status: String,
+    error: Option<String>,
+    next_fetch_in_secs: u64,
+}
+
+#[derive(Clone, Serialize)]
+struct UpstreamCommitsPayload {
+    repo_path: String,
+    branch_name: String,
+    upstream: String,
+    new_commits: usize,
+    behind: usize,
+}
+
+struct RepoSchedule {
+    failures: u32,
+    next_due: Instant,
+}
+
+struct Scheduler {
+    /// Bumped on every restart so a superseded worker thread knows to exit.
+    generation: u64,
+    settings: AutoFetchSettings,
+    schedules: HashMap<String, RepoSchedule>,
+}
+
+static SCHEDULER: Mutex<Option<Scheduler>> = const_mutex(None);
+static WAKE: Condvar = Condvar::new();
+
+enum FetchOutcome {
+    Fetched(Option<UpstreamCommitsPayload>),
+    Offline,
+    Skipped,
+}
+
+/// Keeps the scheduler from spinning on a short interval, whether it came
+/// from the frontend or from an old or hand-edited store.
+fn clamp_settings(settings: AutoFetchSettings) -> AutoFetchSettings {
+    AutoFetchSettings {
+        interval_secs: settings.interval_secs.max(MIN_INTERVAL_SECS),
+        ..settings
+    }
+}
+
+fn load_settings(app: &AppHandle) -> Result<AutoFetchSettings> {
+    let store = app.store(config().STORE_NAME)?;
+    match store.get(config().STORE_AUTO_FETCH_KEY) {
+        Some(value) => Ok(clamp_settings(serde_json::from_value(value)?)),
+        None => Ok(AutoFetchSettings::default()),
+    }
+}
+
+fn save_settings(app: &AppHandle, settings: &AutoFetchSettings) -> Result<()> {
+    let store = app.store(config().STORE_NAME)?;
+    store.set(config().STORE_AUTO_FETCH_KEY, json!(settings));
+    store.save().map_err(|e| Error::StoreError(e.to_string()))?;
+    Ok(())
+}
+
+/// Doubles the interval for every consecutive failure, up to an hour.
+fn backoff_delay(interval_secs: u64, failures: u32) -> Duration {
+    let secs = interval_secs
+        .saturating_mul(1u64 << failures.min(16))
+        .min(MAX_BACKOFF_SECS.max(interval_secs));
+    Duration::from_secs(secs)
+}
+
+fn scheduled_repo_paths(app: &AppHandle, settings: &AutoFetchSettings) -> Result<Vec<String>> {
+    let active = get_last_opened_repo_path(app.clone())?
+        .as_str()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+
+    let mut paths: Vec<String> = active.into_iter().collect();
+    if settings.all_repos {
+        for path in get_repo_paths(app.clone())? {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+
+    Ok(paths)
+}
+
+#[cfg(target_os = "linux")]
+fn read_trimmed(path: &std::path::Path) -> String {
+    std::fs::read_to_string(path)
+        .map(|value| value.trim().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "linux")]
+fn on_battery_power() -> bool {
+    let entries = match std::fs::read_dir("/sys/class/power_supply") {
+        Ok(entries) => entries,
+        Err(_) => return false,
+    };
+
+    let mut has_battery = false;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match read_trimmed(&path.join("type")).as_str() {
+            "Mains" | "USB" if read_trimmed(&path.join("online")) == "1" => return false,
+            "Battery" => has_battery = true,
+            _ => {}
+        }
+    }
+
+    has_battery
+}
+
+#[cfg(target_os = "macos")]
+fn on_battery_power() -> bool {
+    std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains("'Battery Power'"))
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "windows")]
+fn on_battery_power() -> bool {
+    use std::os::windows::process::CommandExt;
+
+    // Keeps a console window from flashing up on every check.
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    // BatteryStatus 1 means the battery is discharging.
+    std::process::Command::new("powershell")
+        .creation_flags(CREATE_NO_WINDOW)
+        .args([
+            "-NoProfile",
+            "-Command",
+            "(Get-CimInstance -ClassName Win32_Battery).BatteryStatus",
+        ])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "1")
+        .unwrap_or(false)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn on_battery_power() -> bool {
+    false
+}
+
+fn upstream_tip(repo: &Repository, remote_name: &str, branch_name: &str) -> Option<Oid> {
+    repo.find_reference(&format!("refs/remotes/{}/{}", remote_name, branch_name))
+        .ok()
+        .and_then(|reference| reference.target())
+}
+
+/// Fetches the upstream remote of the checked out branch, or `origin`.
+fn fetch_repo_in_background(repo_path: &str) -> Result<FetchOutcome> {
+    let repo = Repository::open(repo_path)?;
+
+    let head = repo.head()?;
+    let branch_name = head
+        .shorthand()
+        .filter(|_| head.is_branch())
+        .map(|value| value.to_string());
+    let upstream = branch_name
+        .as_deref()
+        .and_then(|branch_name| upstream_of(&repo, branch_name));
+
+    let remote_name = match &upstream {
+        Some((remote_name, _)) => remote_name.clone(),
+        None if repo.find_remote("origin").is_ok() => "origin".to_string(),
+        None => return Ok(FetchOutcome::Skipped),
+    };
+
+    let before = upstream
+        .as_ref()
+        .and_then(|(remote_name, merge)| upstream_tip(&repo, remote_name, merge));
+
+    // Offline is told from the fetch itself, which goes the same way as any
+    // other fetch, rather than from a probe of our own. Only transport
+    // errors count; local ones like a locked ref or a full disk are failures.
+    match fetch_remote(
+        None,
+        &repo,
+        &remote_name,
+        None,
+        &FetchRequestOptions::default(),
+        None,
+    ) {
+        Ok(_) => {}
+        Err(Error::Git(err))
+            if matches!(
+                err.class(),
+                ErrorClass::Net | ErrorClass::Ssh | ErrorClass::Http
+            ) =>
+        {
+            return Ok(FetchOutcome::Offline)
+        }
+        Err(err) => return Err(err),
+    }
+
+    let (branch_name, (remote_name, merge)) = match (branch_name, upstream) {
+        (Some(branch_name), Some(upstream)) => (branch_name, upstream),
+        _ => return Ok(FetchOutcome::Fetched(None)),
+    };
+    let after = match upstream_tip(&repo, &remote_name, &merge) {
+        Some(after) if Some(after) != before => after,
+        _ => return Ok(FetchOutcome::Fetched(None)),
+    };
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(after)?;
+    if let Some(before) = before {
+        revwalk.hide(before)?;
+    }
+    let new_commits = revwalk.count();
+    if new_commits == 0 {
+        return Ok(FetchOutcome::Fetched(None));
+    }
+
+    let behind = match head.target() {
+        Some(local) => repo.graph_ahead_behind(local, after)?.1,
+        None => new_commits,
+    };
+
+    Ok(FetchOutcome::Fetched(Some(UpstreamCommitsPayload {
+        repo_path: repo_path.to_string(),
+        upstream: format!("{}/{}", remote_name, merge),
+        branch_name,
+        new_commits,
+        behind,
+    })))
+}
+
+fn emit_status(
+    app: &AppHandle,
+    repo_path: Option<&str>,
+    status: &str,
+    error: Option<String>,
+    next_fetch_in: Duration,
+) {
+    let _ = app.emit(
+        "auto-fetch-status",
+        AutoFetchStatusPayload {
+            repo_path: repo_path.map(|value| value.to_string()),
+            status: status.to_string(),
+            error,
+            next_fetch_in_secs: next_fetch_in.as_secs(),
+        },
+    );
+}
+
+/// Settings of the live scheduler, or `None` once `generation` was replaced.
+fn current_settings(generation: u64) -> Option<AutoFetchSettings> {
+    match &*SCHEDULER.lock() {
+        Some(scheduler) if scheduler.generation == generation => Some(scheduler.settings.clone()),
+        _ => None,
+    }
+}
+
+fn run_scheduler(app: AppHandle, generation: u64) {
+    while let Some(settings) = current_settings(generation) {
+        let interval = Duration::from_secs(settings.interval_secs);
+
+        let mut wait = interval;
+        if settings.pause_on_battery && on_battery_power() {
+            emit_status(&app, None, "paused_on_battery", None, interval);
+        } else {
+            let repo_paths = scheduled_repo_paths(&app, &settings).unwrap_or_default();
+            for repo_path in repo_paths {
+                let due = match &*SCHEDULER.lock() {
+                    Some(scheduler) if scheduler.generation == generation => scheduler
+                        .schedules
+                        .get(&repo_path)
+                        .map(|schedule| schedule.next_due <= Instant::now())
+                        .unwrap_or(true),
+                    _ => return,
+                };
+                if !due {
+                    continue;
+                }
+
+                let outcome = fetch_repo_in_background(&repo_path);
+
+                let mut guard = SCHEDULER.lock();
+                let scheduler = match &mut *guard {
+                    Some(scheduler) if scheduler.generation == generation => scheduler,
+                    _ => return,
+                };
+                let schedule =
+                    scheduler
+                        .schedules
+                        .entry(repo_path.clone())
+                        .or_insert(RepoSchedule {
+                            failures: 0,
+                            next_due: Instant::now(),
+                        });
+
+                let (status, error) = match outcome {
+                    Ok(FetchOutcome::Fetched(commits)) => {
+                        schedule.failures = 0;
+                        if let Some(commits) = commits {
+                            let _ = app.emit("upstream-commits", commits);
+                        }
+                        ("fetched", None)
+                    }
+                    Ok(FetchOutcome::Skipped) => {
+                        schedule.failures = 0;
+                        ("skipped", None)
+                    }
+                    // Retried on the normal interval; being offline isn't a
+                    // failure of the remote.
+                    Ok(FetchOutcome::Offline) => ("offline", None),
+                    Err(err) => {
+                        schedule.failures = schedule.failures.saturating_add(1);
+                        ("failed", Some(err.to_string()))
+                    }
+                };
+                let delay = backoff_delay(settings.interval_secs, schedule.failures);
+                schedule.next_due = Instant::now() + delay;
+                drop(guard);
+
+                emit_status(&app, Some(&repo_path), status, error, delay);
+            }
+
+            if let Some(scheduler) = &*SCHEDULER.lock() {
+                let now = Instant::now();
+                if let Some(next_due) = scheduler
+                    .schedules
+                    .values()
+                    .map(|schedule| schedule.next_due)
+                    .min()
+                {
+                    wait = next_due.saturating_duration_since(now).min(interval);
+                }
+            }
+        }
+
+        let mut guard = SCHEDULER.lock();
+        match &*guard {
+            Some(scheduler) if scheduler.generation == generation => {}
+            _ => return,
+        }
+        WAKE.wait_for(&mut guard, wait.max(Duration::from_secs(1)));
+    }
+}
+
+/// Starts or stops the worker thread to match `settings`, replacing any
+/// running one.
+fn apply_settings(app: &AppHandle, settings: AutoFetchSettings) {
+    let mut guard = SCHEDULER.lock();
+    let generation = guard
+        .as_ref()
+        .map(|scheduler| scheduler.generation + 1)
+        .unwrap_or(0);
+
+    if !settings.enabled {
+        *guard = None;
+        WAKE.notify_all();
+        return;
+    }
+
+    *guard = Some(Scheduler {
+        generation,
+        settings,
+        schedules: HashMap::new(),
+    });
+    WAKE.notify_all();
+    drop(guard);
+
+    let app_handle = app.clone();
+    thread::spawn(move || run_scheduler(app_handle, generation));
+}
+
+/// Starts background fetching from the stored settings, called once the app
+/// is set up.
+pub fn start_auto_fetch(app: &AppHandle) -> Result<()> {
+    let settings = load_settings(app)?;
+    apply_settings(app, settings);
+    Ok(())
+}
+
+#[command]
+pub fn get_auto_fetch_settings(app: AppHandle) -> Result<AutoFetchSettings> {
+    load_settings(&app)
+}
+
+#[command]
+pub fn set_auto_fetch_settings(
+    app: AppHandle,
+    settings: AutoFetchSettings,
+) -> Result<AutoFetchSettings> {
+    let settings = clamp_settings(settings);
+
+    save_settings(&app, &settings)?;
+    apply_settings(&app, settings.clone());
+
+    Ok(settings)
+}
+
+/// Makes every scheduled repository due now, e.g. when the window regains
+/// focus.
+#[command]
+pub fn trigger_auto_fetch() {
+    if let Some(scheduler) = &mut *SCHEDULER.lock() {
+        let now = Instant::now();
+        for schedule in scheduler.schedules.values_mut() {
+            schedule.next_due = now;
+        }
+    }
+    WAKE.notify_all();
+}
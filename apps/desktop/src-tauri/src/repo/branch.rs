@@ -1,14 +1,28 @@
-use git2::{build::CheckoutBuilder, BranchType};
+use git2::{build::CheckoutBuilder, Branch, BranchType, Repository};
 use serde::Serialize;
 use tauri::{command, AppHandle};
 
-use crate::{repo::error::Result, repo::open_repo};
+use crate::{
+    repo::checkout::{checkout_blockers, dirty_paths_touched_by, safe_checkout, CheckoutResult},
+    repo::commit::convert_date,
+    repo::error::{Error, Result},
+    repo::open_repo,
+    repo::remote::push_refspecs,
+    repo::status::{conflict_entries, ConflictEntry},
+};
 
 #[derive(Clone, Serialize)]
 pub struct BranchInfo {
     name: String,
     type_of: String,
     is_head: bool,
+    upstream: Option<String>,
+    upstream_gone: bool,
+    ahead: Option<usize>,
+    behind: Option<usize>,
+    tip_oid: Option<String>,
+    tip_summary: Option<String>,
+    tip_date: Option<String>,
 }
 
 #[derive(Clone, Serialize)]
@@ -29,10 +43,138 @@ pub struct FastForwardResult {
 pub struct NormalMergeResult {
     source_branch: String,
     target_branch: String,
-    commit_oid: String,
+    status: String,
+    commit_oid: Option<String>,
+    conflicts: Vec<ConflictEntry>,
 }
 
-fn get_branch_oid(repo: &git2::Repository, branch_name: &str) -> Result<git2::Oid> {
+#[derive(Clone, Serialize)]
+pub struct DeleteBranchResult {
+    branch_name: String,
+    tip_oid: String,
+    was_merged: bool,
+}
+
+#[derive(Clone, Serialize)]
+pub struct CreateBranchResult {
+    branch_name: String,
+    target_oid: String,
+    upstream: Option<String>,
+    checkout: Option<CheckoutResult>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct RenameBranchResult {
+    old_name: String,
+    new_name: String,
+    upstream: Option<String>,
+    /// Why the old remote branch couldn't be deleted after the new one was
+    /// pushed. The rename itself went through.
+    remote_delete_error: Option<String>,
+}
+
+/// The remote name and remote branch name a local branch tracks, if any.
+pub(crate) fn upstream_of(repo: &Repository, branch_name: &str) -> Option<(String, String)> {
+    let ref_name = format!("refs/heads/{}", branch_name);
+    let remote = repo.branch_upstream_remote(&ref_name).ok()?;
+    let merge = repo.branch_upstream_merge(&ref_name).ok()?;
+
+    let remote = remote.as_str()?.to_string();
+    let merge = merge.as_str()?.strip_prefix("refs/heads/")?.to_string();
+    Some((remote, merge))
+}
+
+/// Like git, a branch counts as merged when its tip is contained in its
+/// upstream, or in HEAD when it has none.
+fn is_branch_merged(repo: &Repository, branch: &Branch) -> Result<bool> {
+    let tip = match branch.get().target() {
+        Some(tip) => tip,
+        None => return Ok(true),
+    };
+
+    let base = match branch.upstream() {
+        Ok(upstream) => upstream.get().target(),
+        Err(_) => repo.head()?.target(),
+    };
+
+    Ok(match base {
+        Some(base) => base == tip || repo.graph_descendant_of(base, tip)?,
+        None => false,
+    })
+}
+
+/// Points `branch_name` at `remote_name/remote_branch` through the config,
+/// which unlike `Branch::set_upstream` does not require the remote-tracking
+/// ref to exist yet.
+pub(crate) fn write_upstream_config(
+    repo: &Repository,
+    branch_name: &str,
+    remote_name: &str,
+    remote_branch: &str,
+) -> Result<()> {
+    let mut config = repo.config()?;
+    config.set_str(&format!("branch.{}.remote", branch_name), remote_name)?;
+    config.set_str(
+        &format!("branch.{}.merge", branch_name),
+        &format!("refs/heads/{}", remote_branch),
+    )?;
+    Ok(())
+}
+
+/// Checks `name` against git's ref-name rules, explaining the first one it
+/// breaks.
+pub(crate) fn validate_branch_name(name: &str) -> Result<()> {
+    let invalid = |reason: &str| {
+        Err(Error::BranchError(format!(
+            "\"{}\" is not a valid branch name: {}",
+            name, reason
+        )))
+    };
+
+    if name.trim().is_empty() {
+        return Err(Error::BranchError(
+            "Branch name cannot be empty".to_string(),
+        ));
+    }
+    if name.starts_with('-') {
+        return invalid("it cannot start with '-'");
+    }
+    if name == "@" || name == "HEAD" {
+        return invalid("it is reserved");
+    }
+    if let Some(ch) = name
+        .chars()
+        .find(|ch| ch.is_control() || " ~^:?*[\\".contains(*ch))
+    {
+        return invalid(&format!("it cannot contain {:?}", ch));
+    }
+    if name.contains("..") || name.contains("@{") || name.contains("//") {
+        return invalid("it cannot contain '..', '@{' or '//'");
+    }
+    if name.starts_with('/') || name.ends_with('/') || name.ends_with('.') {
+        return invalid("it cannot start or end with '/' or end with '.'");
+    }
+    if name
+        .split('/')
+        .any(|component| component.starts_with('.') || component.ends_with(".lock"))
+    {
+        return invalid("no path component can start with '.' or end with '.lock'");
+    }
+    if !Branch::name_is_valid(name)? {
+        return invalid("it is not a valid ref name");
+    }
+
+    Ok(())
+}
+
+fn blocked_by_local_changes(paths: &[String]) -> Error {
+    Error::BranchError(format!(
+        "Local changes would be overwritten: {}",
+        paths.join(", ")
+    ))
+}
+
+pub(crate) fn get_branch_oid(repo: &git2::Repository, branch_name: &str) -> Result<git2::Oid> {
     let ref_name = format!("refs/heads/{}", branch_name);
     let reference = repo.find_reference(&ref_name)?;
     reference.target().ok_or_else(|| {
@@ -66,10 +208,49 @@ pub fn list_branches(app: AppHandle, repo_path: Option<String>) -> Result<Vec<Br
                 BranchType::Remote => ("Remote", false),
             };
 
+            let tip = branch.get().peel_to_commit().ok();
+            let mut upstream = None;
+            let mut upstream_gone = false;
+            let mut ahead = None;
+            let mut behind = None;
+
+            if branch_type == BranchType::Local {
+                match branch.upstream() {
+                    Ok(upstream_branch) => {
+                        upstream = upstream_branch.name()?.map(|value| value.to_string());
+                        if let (Some(local_oid), Some(upstream_oid)) =
+                            (branch.get().target(), upstream_branch.get().target())
+                        {
+                            let (ahead_count, behind_count) =
+                                repo.graph_ahead_behind(local_oid, upstream_oid)?;
+                            ahead = Some(ahead_count);
+                            behind = Some(behind_count);
+                        }
+                    }
+                    // Configured but missing remote-tracking ref: the upstream
+                    // was deleted and pruned.
+                    Err(_) => {
+                        if let Some((remote, remote_branch)) = upstream_of(&repo, name) {
+                            upstream = Some(format!("{}/{}", remote, remote_branch));
+                            upstream_gone = true;
+                        }
+                    }
+                }
+            }
+
             all_branches.push(BranchInfo {
                 name: name.to_string(),
                 type_of: bran_type.to_string(),
                 is_head,
+                upstream,
+                upstream_gone,
+                ahead,
+                behind,
+                tip_oid: tip.as_ref().map(|commit| commit.id().to_string()),
+                tip_summary: tip
+                    .as_ref()
+                    .and_then(|commit| commit.summary().map(|value| value.to_string())),
+                tip_date: tip.as_ref().map(|commit| convert_date(commit.time())),
             });
         }
     }
@@ -78,33 +259,181 @@ pub fn list_branches(app: AppHandle, repo_path: Option<String>) -> Result<Vec<Br
 }
 
 #[command]
-pub fn create_branch(app: AppHandle, repo_path: Option<String>, branch_name: String) -> Result<()> {
-    let repo = open_repo(app.clone(), repo_path)?;
+pub fn create_branch(
+    app: AppHandle,
+    repo_path: Option<String>,
+    branch_name: String,
+    start_point: Option<String>,
+    checkout: Option<bool>,
+    mode: Option<String>,
+) -> Result<CreateBranchResult> {
+    validate_branch_name(&branch_name)?;
 
-    let head = repo.head()?;
-    let commit = head.peel_to_commit()?;
+    let mut repo = open_repo(app.clone(), repo_path)?;
 
-    repo.branch(&branch_name, &commit, false)?;
+    if repo.find_branch(&branch_name, BranchType::Local).is_ok() {
+        return Err(Error::BranchError(format!(
+            "A branch named {} already exists",
+            branch_name
+        )));
+    }
 
-    Ok(())
+    let start_point = start_point
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+
+    let target_oid = match &start_point {
+        Some(start_point) => repo.revparse_single(start_point)?.peel_to_commit()?.id(),
+        None => repo.head()?.peel_to_commit()?.id(),
+    };
+
+    let mut upstream = None;
+    {
+        let mut branch = repo.branch(&branch_name, &repo.find_commit(target_oid)?, false)?;
+
+        // Like git's branch.autoSetupMerge, branching off a remote-tracking
+        // branch tracks it.
+        if let Some(start_point) = &start_point {
+            if repo.find_branch(start_point, BranchType::Remote).is_ok() {
+                branch.set_upstream(Some(start_point))?;
+                upstream = Some(start_point.clone());
+            }
+        }
+    }
+
+    let checkout = if checkout.unwrap_or(false) {
+        let ref_name = format!("refs/heads/{}", branch_name);
+        Some(safe_checkout(
+            &mut repo,
+            target_oid,
+            Some(&ref_name),
+            &branch_name,
+            mode.as_deref(),
+        )?)
+    } else {
+        None
+    };
+
+    Ok(CreateBranchResult {
+        branch_name,
+        target_oid: target_oid.to_string(),
+        upstream,
+        checkout,
+    })
 }
 
+/// `mode` is one of `carry_over` (default), `auto_stash` or `abort`; see
+/// `repo::checkout` for how each treats uncommitted changes.
 #[command]
 pub fn checkout_branch(
     app: AppHandle,
     repo_path: Option<String>,
     branch_name: String,
-) -> Result<()> {
-    let repo = open_repo(app.clone(), repo_path.clone())?;
+    mode: Option<String>,
+) -> Result<CheckoutResult> {
+    let mut repo = open_repo(app.clone(), repo_path.clone())?;
 
     let ref_name = format!("refs/heads/{}", branch_name);
 
-    let obj = repo.revparse_single(&ref_name)?;
+    let target_oid = repo.revparse_single(&ref_name)?.peel_to_commit()?.id();
 
-    repo.checkout_tree(&obj, None)?;
-    repo.set_head(&ref_name)?;
+    safe_checkout(
+        &mut repo,
+        target_oid,
+        Some(&ref_name),
+        &branch_name,
+        mode.as_deref(),
+    )
+}
 
-    Ok(())
+/// Creates a local branch from a remote-tracking branch such as
+/// `origin/feature`, tracks it and checks it out. The local name defaults to
+/// the remote branch name without the remote prefix.
+#[command]
+pub fn checkout_remote_branch(
+    app: AppHandle,
+    repo_path: Option<String>,
+    remote_branch: String,
+    local_name: Option<String>,
+    mode: Option<String>,
+) -> Result<CheckoutResult> {
+    let mut repo = open_repo(app.clone(), repo_path)?;
+
+    let (remote_name, tip_oid) = {
+        let branch = repo.find_branch(&remote_branch, BranchType::Remote)?;
+        let ref_name = branch
+            .get()
+            .name()
+            .ok_or_else(|| Error::BranchError("Invalid remote branch name".to_string()))?;
+        let remote_name = repo.branch_remote_name(ref_name)?;
+        let remote_name = remote_name
+            .as_str()
+            .ok_or_else(|| Error::BranchError("Invalid remote name".to_string()))?
+            .to_string();
+        (remote_name, branch.get().peel_to_commit()?.id())
+    };
+
+    let local_name = local_name
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| {
+            remote_branch
+                .strip_prefix(&format!("{}/", remote_name))
+                .unwrap_or(&remote_branch)
+                .to_string()
+        });
+    validate_branch_name(&local_name)?;
+
+    if repo.find_branch(&local_name, BranchType::Local).is_ok() {
+        return Err(Error::BranchError(format!(
+            "A local branch named {} already exists",
+            local_name
+        )));
+    }
+
+    {
+        let mut branch = repo.branch(&local_name, &repo.find_commit(tip_oid)?, false)?;
+        branch.set_upstream(Some(&remote_branch))?;
+    }
+
+    let ref_name = format!("refs/heads/{}", local_name);
+    let checked_out = safe_checkout(
+        &mut repo,
+        tip_oid,
+        Some(&ref_name),
+        &local_name,
+        mode.as_deref(),
+    );
+
+    // Don't leave a half-done branch behind when the checkout was refused or
+    // failed before HEAD moved to it.
+    let refused = match &checked_out {
+        Ok(result) => result.status == "blocked",
+        Err(_) => true,
+    };
+    if refused {
+        let mut branch = repo.find_branch(&local_name, BranchType::Local)?;
+        if !branch.is_head() {
+            branch.delete()?;
+        }
+    }
+
+    checked_out
+}
+
+/// Checks out any commit-ish (oid, tag, remote branch...) with a detached HEAD.
+#[command]
+pub fn checkout_detached(
+    app: AppHandle,
+    repo_path: Option<String>,
+    target: String,
+    mode: Option<String>,
+) -> Result<CheckoutResult> {
+    let mut repo = open_repo(app.clone(), repo_path)?;
+
+    let target_oid = repo.revparse_single(&target)?.peel_to_commit()?.id();
+
+    safe_checkout(&mut repo, target_oid, None, &target, mode.as_deref())
 }
 
 #[command]
@@ -160,8 +489,6 @@ pub fn fast_forward(
     }
 
     let target_ref_name = format!("refs/heads/{}", target_branch);
-    let mut target_ref = repo.find_reference(&target_ref_name)?;
-    target_ref.set_target(source_oid, "fast-forward")?;
 
     let should_checkout = repo
         .head()
@@ -170,11 +497,25 @@ pub fn fast_forward(
         .map(|head_branch| head_branch == target_branch)
         .unwrap_or(false);
 
+    // The working tree has to move before the ref does, otherwise a safe
+    // checkout would see every fast-forwarded file as a local edit.
     if should_checkout {
-        repo.set_head(&target_ref_name)?;
+        let blockers = checkout_blockers(&repo, source_oid)?;
+        if !blockers.is_empty() {
+            return Err(blocked_by_local_changes(&blockers));
+        }
+
+        let source_commit = repo.find_commit(source_oid)?;
         let mut checkout_builder = CheckoutBuilder::new();
-        checkout_builder.force();
-        repo.checkout_head(Some(&mut checkout_builder))?;
+        checkout_builder.safe();
+        repo.checkout_tree(source_commit.as_object(), Some(&mut checkout_builder))?;
+    }
+
+    let mut target_ref = repo.find_reference(&target_ref_name)?;
+    target_ref.set_target(source_oid, "fast-forward")?;
+
+    if should_checkout {
+        repo.set_head(&target_ref_name)?;
     }
 
     Ok(FastForwardResult {
@@ -207,16 +548,33 @@ pub fn normal_merge(
     let source_ref = repo.find_reference(&source_ref_name)?;
     let source_annotated = repo.reference_to_annotated_commit(&source_ref)?;
 
+    let blockers = dirty_paths_touched_by(
+        &repo,
+        Some(&repo.find_commit(merge_base)?.tree()?),
+        &repo.find_commit(source_oid)?.tree()?,
+    )?;
+    if !blockers.is_empty() {
+        return Err(blocked_by_local_changes(&blockers));
+    }
+
     let mut checkout_builder = CheckoutBuilder::new();
-    checkout_builder.force();
+    checkout_builder
+        .safe()
+        .allow_conflicts(true)
+        .conflict_style_merge(true);
     repo.merge(&[&source_annotated], None, Some(&mut checkout_builder))?;
 
+    // Conflicts leave the merge in progress (MERGE_HEAD, MERGE_MSG) so they
+    // can be resolved and concluded through the `repo::merge` commands.
     let mut index = repo.index()?;
     if index.has_conflicts() {
-        repo.cleanup_state()?;
-        return Err(crate::repo::error::Error::RepoOpeningError(
-            "Merge resulted in conflicts. Resolve manually.".to_string(),
-        ));
+        return Ok(NormalMergeResult {
+            source_branch,
+            target_branch,
+            status: "conflicts".to_string(),
+            commit_oid: None,
+            conflicts: conflict_entries(&index)?,
+        });
     }
 
     let tree_oid = index.write_tree_to(&repo)?;
@@ -248,13 +606,201 @@ pub fn normal_merge(
 
     if should_checkout {
         let mut post_merge_checkout = CheckoutBuilder::new();
-        post_merge_checkout.force();
+        post_merge_checkout.safe();
         repo.checkout_head(Some(&mut post_merge_checkout))?;
     }
 
     Ok(NormalMergeResult {
         source_branch,
         target_branch,
-        commit_oid: commit_oid.to_string(),
+        status: "merged".to_string(),
+        commit_oid: Some(commit_oid.to_string()),
+        conflicts: Vec::new(),
+    })
+}
+
+#[command]
+pub fn delete_branch(
+    app: AppHandle,
+    repo_path: Option<String>,
+    branch_name: String,
+    force: bool,
+) -> Result<DeleteBranchResult> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    let mut branch = repo.find_branch(&branch_name, BranchType::Local)?;
+
+    if branch.is_head() {
+        return Err(Error::BranchError(format!(
+            "Cannot delete {} while it is checked out",
+            branch_name
+        )));
+    }
+
+    let tip_oid = get_branch_oid(&repo, &branch_name)?;
+    let was_merged = is_branch_merged(&repo, &branch)?;
+    if !was_merged && !force {
+        return Err(Error::BranchError(format!(
+            "The branch {} is not fully merged",
+            branch_name
+        )));
+    }
+
+    branch.delete()?;
+
+    Ok(DeleteBranchResult {
+        branch_name,
+        tip_oid: tip_oid.to_string(),
+        was_merged,
     })
 }
+
+/// Deletes a branch on its remote, e.g. `origin/feature`, by pushing an empty
+/// source to it.
+#[command]
+pub fn delete_remote_branch(
+    app: AppHandle,
+    repo_path: Option<String>,
+    remote_branch: String,
+    token: Option<String>,
+) -> Result<()> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    let branch = repo.find_branch(&remote_branch, BranchType::Remote)?;
+
+    let ref_name = branch
+        .get()
+        .name()
+        .ok_or_else(|| Error::BranchError("Invalid remote branch name".to_string()))?
+        .to_string();
+    let remote_name = repo.branch_remote_name(&ref_name)?;
+    let remote_name = remote_name
+        .as_str()
+        .ok_or_else(|| Error::BranchError("Invalid remote name".to_string()))?
+        .to_string();
+    let branch_name = remote_branch
+        .strip_prefix(&format!("{}/", remote_name))
+        .unwrap_or(&remote_branch)
+        .to_string();
+
+    push_refspecs(
+        &repo,
+        &remote_name,
+        &[format!(":refs/heads/{}", branch_name)],
+        token,
+    )?;
+
+    if let Ok(mut tracking) = repo.find_branch(&remote_branch, BranchType::Remote) {
+        tracking.delete()?;
+    }
+
+    Ok(())
+}
+
+/// Renames a local branch. With `rename_remote`, the upstream branch is
+/// pushed under the new name, tracking moved over and the old one deleted.
+#[command]
+pub fn rename_branch(
+    app: AppHandle,
+    repo_path: Option<String>,
+    old_name: String,
+    new_name: String,
+    rename_remote: bool,
+    token: Option<String>,
+) -> Result<RenameBranchResult> {
+    let repo = open_repo(app.clone(), repo_path)?;
+
+    validate_branch_name(&new_name)?;
+
+    let upstream = upstream_of(&repo, &old_name);
+    let mut branch = repo.find_branch(&old_name, BranchType::Local)?;
+    branch.rename(&new_name, false)?;
+
+    let mut upstream_name = upstream
+        .as_ref()
+        .map(|(remote, branch)| format!("{}/{}", remote, branch));
+    let mut remote_delete_error = None;
+
+    if rename_remote {
+        if let Some((remote_name, remote_branch)) = upstream {
+            let pushed = push_refspecs(
+                &repo,
+                &remote_name,
+                &[format!("refs/heads/{0}:refs/heads/{0}", new_name)],
+                token.clone(),
+            );
+            // Keep the local name in step with the remote one.
+            if let Err(err) = pushed {
+                repo.find_branch(&new_name, BranchType::Local)?
+                    .rename(&old_name, false)?;
+                return Err(err);
+            }
+
+            write_upstream_config(&repo, &new_name, &remote_name, &new_name)?;
+            upstream_name = Some(format!("{}/{}", remote_name, new_name));
+
+            // The new name is already on the remote, so a failed delete
+            // only leaves the old name behind as well.
+            match push_refspecs(
+                &repo,
+                &remote_name,
+                &[format!(":refs/heads/{}", remote_branch)],
+                token,
+            ) {
+                Ok(()) => {
+                    if let Ok(mut tracking) = repo.find_branch(
+                        &format!("{}/{}", remote_name, remote_branch),
+                        BranchType::Remote,
+                    ) {
+                        tracking.delete()?;
+                    }
+                }
+                Err(err) => remote_delete_error = Some(err.to_string()),
+            }
+        }
+    }
+
+    Ok(RenameBranchResult {
+        old_name,
+        new_name,
+        upstream: upstream_name,
+        remote_delete_error,
+    })
+}
+
+/// Sets the upstream of `branch_name` to a remote branch like
+/// `origin/feature`, or unsets it when `upstream` is `None`.
+#[command]
+pub fn set_branch_upstream(
+    app: AppHandle,
+    repo_path: Option<String>,
+    branch_name: String,
+    upstream: Option<String>,
+) -> Result<()> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    let mut branch = repo.find_branch(&branch_name, BranchType::Local)?;
+
+    match upstream {
+        Some(upstream) => {
+            if repo.find_branch(&upstream, BranchType::Remote).is_ok() {
+                branch.set_upstream(Some(&upstream))?;
+            } else {
+                let (remote_name, remote_branch) = upstream.split_once('/').ok_or_else(|| {
+                    Error::BranchError(format!("{} is not a remote branch", upstream))
+                })?;
+                repo.find_remote(remote_name)?;
+                write_upstream_config(&repo, &branch_name, remote_name, remote_branch)?;
+            }
+        }
+        // Unsetting also clears the config of an upstream that is gone.
+        None => {
+            if let Err(err) = branch.set_upstream(None) {
+                // libgit2 fails when there was no upstream to unset.
+                let key = format!("branch.{}.remote", branch_name);
+                if repo.config()?.get_entry(&key).is_ok() {
+                    return Err(err.into());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,208 @@
+use std::collections::HashSet;
+
+use git2::{build::CheckoutBuilder, ErrorCode, Oid, Repository, StashFlags, StatusOptions, Tree};
+use serde::Serialize;
+use tauri::{command, AppHandle};
+
+use crate::repo::{
+    error::{Error, Result},
+    open_repo,
+    stash::{pop_stash_entry, StashApplyResult},
+};
+
+#[derive(Clone, Copy, PartialEq)]
+enum CheckoutMode {
+    /// Keep local changes that don't collide with the target, refuse otherwise.
+    CarryOver,
+    /// Stash everything, check out, then reapply the stash.
+    AutoStash,
+    /// Refuse as soon as there are any uncommitted changes.
+    Abort,
+}
+
+#[derive(Clone, Serialize)]
+pub struct CheckoutResult {
+    pub(crate) status: String,
+    target: String,
+    blocking_paths: Vec<String>,
+    stash: Option<StashApplyResult>,
+}
+
+fn parse_mode(mode: Option<&str>) -> Result<CheckoutMode> {
+    match mode.unwrap_or("carry_over") {
+        "carry_over" => Ok(CheckoutMode::CarryOver),
+        "auto_stash" => Ok(CheckoutMode::AutoStash),
+        "abort" => Ok(CheckoutMode::Abort),
+        other => Err(Error::BranchError(format!(
+            "Unknown checkout mode: {}",
+            other
+        ))),
+    }
+}
+
+pub(crate) fn status_paths(repo: &Repository, include_untracked: bool) -> Result<Vec<String>> {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(include_untracked)
+        .recurse_untracked_dirs(include_untracked)
+        .include_ignored(false);
+
+    let statuses = repo.statuses(Some(&mut opts))?;
+    Ok(statuses
+        .iter()
+        .filter_map(|entry| entry.path().map(|path| path.to_string()))
+        .collect())
+}
+
+/// Paths whose local changes a checkout of `target_oid` would overwrite, the
+/// same ones `git checkout` lists before refusing.
+pub(crate) fn checkout_blockers(repo: &Repository, target_oid: Oid) -> Result<Vec<String>> {
+    let target_tree = repo.find_commit(target_oid)?.tree()?;
+    let head_tree = match repo.head() {
+        Ok(head) => Some(head.peel_to_tree()?),
+        Err(err) if matches!(err.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => None,
+        Err(err) => return Err(err.into()),
+    };
+
+    dirty_paths_touched_by(repo, head_tree.as_ref(), &target_tree)
+}
+
+/// Local changes, untracked files included, on paths that differ between
+/// `from` and `to`, i.e. what a merge or fast-forward between them would touch.
+pub(crate) fn dirty_paths_touched_by(
+    repo: &Repository,
+    from: Option<&Tree>,
+    to: &Tree,
+) -> Result<Vec<String>> {
+    let diff = repo.diff_tree_to_tree(from, Some(to), None)?;
+    let touched: HashSet<String> = diff
+        .deltas()
+        .flat_map(|delta| [delta.old_file().path(), delta.new_file().path()])
+        .flatten()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+
+    Ok(status_paths(repo, true)?
+        .into_iter()
+        .filter(|path| touched.contains(path))
+        .collect())
+}
+
+fn checkout_target(repo: &Repository, target_oid: Oid, head_ref: Option<&str>) -> Result<()> {
+    let target = repo.find_commit(target_oid)?;
+    let mut checkout_builder = CheckoutBuilder::new();
+    checkout_builder.safe();
+    repo.checkout_tree(target.as_object(), Some(&mut checkout_builder))?;
+
+    match head_ref {
+        Some(head_ref) => repo.set_head(head_ref)?,
+        None => repo.set_head_detached(target_oid)?,
+    }
+    Ok(())
+}
+
+/// Puts the working tree back on HEAD and pops the auto-stash after a failed
+/// checkout, so the changes don't end up buried in the stash list.
+fn restore_auto_stash(repo: &mut Repository) {
+    let mut checkout_builder = CheckoutBuilder::new();
+    checkout_builder.force();
+    let restored = repo
+        .checkout_head(Some(&mut checkout_builder))
+        .map_err(Error::from)
+        .and_then(|_| pop_stash_entry(repo, 0, false));
+    if let Err(err) = restored {
+        log::warn!(
+            "Could not reapply the auto-stash, it is kept as stash@{{0}}: {}",
+            err
+        );
+    }
+}
+
+/// Checks out `target_oid` without losing uncommitted work, then points HEAD
+/// at `head_ref`, or detaches it when that is `None`.
+pub(crate) fn safe_checkout(
+    repo: &mut Repository,
+    target_oid: Oid,
+    head_ref: Option<&str>,
+    target_name: &str,
+    mode: Option<&str>,
+) -> Result<CheckoutResult> {
+    let mode = parse_mode(mode)?;
+
+    let blocked = |paths: Vec<String>| CheckoutResult {
+        status: "blocked".to_string(),
+        target: target_name.to_string(),
+        blocking_paths: paths,
+        stash: None,
+    };
+
+    let mut stashed = false;
+    match mode {
+        CheckoutMode::Abort => {
+            let dirty = status_paths(repo, false)?;
+            if !dirty.is_empty() {
+                return Ok(blocked(dirty));
+            }
+        }
+        CheckoutMode::CarryOver => {
+            let blockers = checkout_blockers(repo, target_oid)?;
+            if !blockers.is_empty() {
+                return Ok(blocked(blockers));
+            }
+        }
+        CheckoutMode::AutoStash => {
+            let signature = repo.signature()?;
+            let message = format!("auto-stash before checking out {}", target_name);
+            match repo.stash_save2(
+                &signature,
+                Some(&message),
+                Some(StashFlags::INCLUDE_UNTRACKED),
+            ) {
+                Ok(_) => stashed = true,
+                Err(err) if err.code() == ErrorCode::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    if let Err(err) = checkout_target(repo, target_oid, head_ref) {
+        if stashed {
+            restore_auto_stash(repo);
+        }
+        return Err(err);
+    }
+
+    let stash = if stashed {
+        Some(pop_stash_entry(repo, 0, false)?)
+    } else {
+        None
+    };
+
+    let status = match &stash {
+        Some(stash) if stash.status != "applied" => "conflicts",
+        _ => "checked_out",
+    };
+
+    Ok(CheckoutResult {
+        status: status.to_string(),
+        target: target_name.to_string(),
+        blocking_paths: Vec::new(),
+        stash,
+    })
+}
+
+/// Lists the paths that would stop a checkout of `branch_name` from carrying
+/// local changes over.
+#[command]
+pub fn get_checkout_blockers(
+    app: AppHandle,
+    repo_path: Option<String>,
+    branch_name: String,
+) -> Result<Vec<String>> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    let target_oid = repo
+        .revparse_single(&format!("refs/heads/{}", branch_name))?
+        .peel_to_commit()?
+        .id();
+
+    checkout_blockers(&repo, target_oid)
+}
@@ -1,48 +1,126 @@
-use std::{
-    path::Path,
-    sync::Arc,
-};
+use std::{fs, io, path::Path, sync::Arc};
 
-use git2::{build::RepoBuilder, FetchOptions, RemoteCallbacks};
+use git2::{
+    build::{CheckoutBuilder, RepoBuilder},
+    FetchOptions, RemoteCallbacks, Repository, SubmoduleUpdateOptions,
+};
 use parking_lot::Mutex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tauri::{command, AppHandle, Emitter};
 
-use crate::repo::save_repos_in_store;
+use crate::repo::credentials::CredentialProvider;
 use crate::repo::error::{Error, Result};
+use crate::repo::operation::{run_blocking, start_operation, Operation};
+use crate::repo::remote::{remote_endpoint, stop_when_cancelled};
+use crate::repo::save_repos_in_store;
+use crate::repo::ssh::RemoteAuth;
+
+const MIRROR_REFSPEC: &str = "+refs/*:refs/*";
 
 #[derive(Clone, Serialize)]
 struct CloneProgressPayload {
+    operation_id: String,
     phase: String,
     value: usize,
+    current: usize,
+    total: usize,
 }
 
-#[command]
-pub async fn clone_repo(url: String, file_path: String, app: AppHandle) -> Result<()> {
-    let app_handle = app.clone();
-    let mut callbacks = RemoteCallbacks::new();
-    let progress = Arc::new(Mutex::new(0));
+#[derive(Clone, Default, Deserialize)]
+pub struct CloneRequestOptions {
+    /// Branch to check out instead of the remote's default.
+    pub branch: Option<String>,
+    /// Shallow clone of this many commits.
+    pub depth: Option<i32>,
+    pub recurse_submodules: Option<bool>,
+    pub bare: Option<bool>,
+    /// Bare clone that copies every ref and keeps them in sync on fetch.
+    pub mirror: Option<bool>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct CloneResult {
+    status: String,
+    path: String,
+    operation_id: String,
+    bare: bool,
+    submodules: Vec<String>,
+    credential_method: Option<String>,
+    /// Why updating submodules stopped. The clone itself is kept.
+    submodule_error: Option<String>,
+}
+
+/// Emits `clone-progress`, once per percent and whenever the phase changes.
+#[derive(Clone)]
+struct CloneProgress {
+    app: AppHandle,
+    operation_id: String,
+    last: Arc<Mutex<(String, usize)>>,
+}
+
+impl CloneProgress {
+    fn report(&self, phase: &str, current: usize, total: usize) {
+        if total == 0 {
+            return;
+        }
+
+        let percent = (current * 100) / total;
+        let mut last = self.last.lock();
+        if last.0 != phase || percent > last.1 {
+            *last = (phase.to_string(), percent);
+
+            let _ = self.app.emit(
+                "clone-progress",
+                CloneProgressPayload {
+                    operation_id: self.operation_id.clone(),
+                    phase: phase.to_string(),
+                    value: percent,
+                    current,
+                    total,
+                },
+            );
+        }
+    }
+}
+
+fn url_credentials(url: &str, token: Option<String>) -> CredentialProvider {
+    let auth = RemoteAuth {
+        url: url.to_string(),
+        ..RemoteAuth::default()
+    };
+    CredentialProvider::new(token, auth, None)
+}
+
+/// Credentials, cancellation and transfer progress for one clone or
+/// submodule fetch.
+fn clone_fetch_options<'a>(
+    provider: &CredentialProvider,
+    operation: &Operation,
+    progress: &CloneProgress,
+    depth: Option<i32>,
+) -> FetchOptions<'a> {
+    let mut callbacks: RemoteCallbacks<'a> = provider.callbacks();
+    stop_when_cancelled(&mut callbacks, operation);
 
+    let progress_operation = operation.clone();
+    let transfer_progress = progress.clone();
     callbacks.transfer_progress(move |stats| {
-        let total = stats.total_objects();
-        let current = stats.received_objects();
-
-        if total > 0 {
-            let percent = (current * 100) / total;
-
-            let mut last_percent = progress.lock();
-            if percent > *last_percent {
-                *last_percent = percent;
-
-                // emit the event
-                let _ = app_handle.emit(
-                    "clone-progress",
-                    CloneProgressPayload {
-                        phase: "Downloading Objects".to_string(),
-                        value: percent,
-                    },
-                );
-            }
+        if progress_operation.is_cancelled() {
+            return false; // makes libgit2 abort the clone
+        }
+
+        if stats.received_objects() < stats.total_objects() {
+            transfer_progress.report(
+                "Receiving objects",
+                stats.received_objects(),
+                stats.total_objects(),
+            );
+        } else {
+            transfer_progress.report(
+                "Resolving deltas",
+                stats.indexed_deltas(),
+                stats.total_deltas(),
+            );
         }
 
         true // continue downloading
@@ -50,15 +128,315 @@ pub async fn clone_repo(url: String, file_path: String, app: AppHandle) -> Resul
 
     let mut fetch_options = FetchOptions::new();
     fetch_options.remote_callbacks(callbacks);
+    if let Some(depth) = depth {
+        fetch_options.depth(depth);
+    }
+    fetch_options
+}
+
+fn checkout_progress<'a>(progress: &CloneProgress) -> CheckoutBuilder<'a> {
+    let checkout_progress = progress.clone();
+    let mut checkout_builder = CheckoutBuilder::new();
+    checkout_builder.progress(move |_path, current, total| {
+        checkout_progress.report("Checking out files", current, total);
+    });
+    checkout_builder
+}
+
+/// Resolves a `./` or `../` submodule URL against the URL of the repository
+/// that declares it, the way git does.
+fn resolve_submodule_url(parent_url: &str, url: &str) -> String {
+    if !url.starts_with("./") && !url.starts_with("../") {
+        return url.to_string();
+    }
+
+    let mut base = parent_url.trim_end_matches('/').to_string();
+    let mut rest = url;
+    loop {
+        if let Some(stripped) = rest.strip_prefix("./") {
+            rest = stripped;
+        } else if let Some(stripped) = rest.strip_prefix("../") {
+            rest = stripped;
+            // The colon of scp-like URLs stays, `host:` can't go higher.
+            if let Some(idx) = base.rfind(['/', ':']) {
+                let keep = if base[idx..].starts_with(':') {
+                    idx + 1
+                } else {
+                    idx
+                };
+                base.truncate(keep);
+            }
+        } else {
+            break;
+        }
+    }
+
+    let separator = if base.ends_with(':') { "" } else { "/" };
+    format!("{}{}{}", base, separator, rest)
+}
+
+/// The token was issued for the superproject's host, so a submodule on
+/// another host, or one fetched over plain HTTP, doesn't get it; it can
+/// still use an account saved for its own host.
+fn submodule_token(
+    superproject_url: &str,
+    submodule_url: &str,
+    token: &Option<String>,
+) -> Option<String> {
+    let host = |url: &str| remote_endpoint(url).map(|(host, _)| host.to_lowercase());
+
+    let same_host = host(submodule_url).is_some() && host(submodule_url) == host(superproject_url);
+    if !same_host || submodule_url.starts_with("http://") {
+        return None;
+    }
+    token.clone()
+}
+
+/// Initialises and clones every submodule, then theirs. Returns the paths
+/// relative to the top-level repository.
+fn update_submodules(
+    repo: &Repository,
+    prefix: &Path,
+    urls: (&str, &str),
+    token: &Option<String>,
+    operation: &Operation,
+    progress: &CloneProgress,
+    updated: &mut Vec<String>,
+) -> Result<()> {
+    let (superproject_url, parent_url) = urls;
+    let mut submodules = repo.submodules()?;
+    let total = submodules.len();
+
+    for (idx, submodule) in submodules.iter_mut().enumerate() {
+        if operation.is_cancelled() {
+            return Err(Error::Cancelled(operation.id().to_string()));
+        }
+        progress.report("Updating submodules", idx, total);
+
+        let url = resolve_submodule_url(parent_url, submodule.url().unwrap_or_default());
+        let provider = url_credentials(&url, submodule_token(superproject_url, &url, token));
+        let mut update_options = SubmoduleUpdateOptions::new();
+        update_options
+            .fetch(clone_fetch_options(&provider, operation, progress, None))
+            .checkout(checkout_progress(progress))
+            .allow_fetch(true);
+        operation.check(
+            submodule
+                .update(true, Some(&mut update_options))
+                .inspect_err(|err| provider.failed(err))
+                .map_err(Error::from),
+        )?;
+        provider.succeeded();
+
+        let path = prefix.join(submodule.path());
+        updated.push(path.to_string_lossy().to_string());
+
+        let submodule_repo = submodule.open()?;
+        update_submodules(
+            &submodule_repo,
+            &path,
+            (superproject_url, &url),
+            token,
+            operation,
+            progress,
+            updated,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Removes what a failed clone left behind. git only clones into a missing
+/// or empty directory, so anything in it came from the clone; a directory
+/// that already existed is emptied but kept.
+fn remove_partial_clone(path: &Path, created: bool) -> io::Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    if created {
+        return fs::remove_dir_all(path);
+    }
+
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            fs::remove_dir_all(entry.path())?;
+        } else {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+fn clone_with_options(
+    url: &str,
+    target: &Path,
+    token: Option<String>,
+    options: &CloneRequestOptions,
+    operation: &Operation,
+    progress: &CloneProgress,
+) -> Result<(Repository, Option<String>)> {
+    let mirror = options.mirror.unwrap_or(false);
+    let bare = mirror || options.bare.unwrap_or(false);
+    let provider = url_credentials(url, token);
 
     let mut builder = RepoBuilder::new();
-    builder.fetch_options(fetch_options);
+    builder.bare(bare).fetch_options(clone_fetch_options(
+        &provider,
+        operation,
+        progress,
+        options.depth,
+    ));
+    if let Some(branch) = options.branch.as_deref().filter(|value| !value.is_empty()) {
+        builder.branch(branch);
+    }
+    if mirror {
+        builder.remote_create(|repo, name, url| {
+            let remote = repo.remote_with_fetch(name, url, MIRROR_REFSPEC)?;
+            repo.config()?
+                .set_bool(&format!("remote.{}.mirror", name), true)?;
+            Ok(remote)
+        });
+    }
+    if !bare {
+        builder.with_checkout(checkout_progress(progress));
+    }
+
+    let repo = operation.check(
+        builder
+            .clone(url, target)
+            .inspect_err(|err| provider.failed(err))
+            .map_err(Error::from),
+    )?;
+    let credential_method = provider.succeeded();
+
+    Ok((repo, credential_method))
+}
+
+/// Clones `url` into `file_path`. Credentials come from the `token`, git
+/// credential helpers or the stored token over HTTPS, and SSH keys otherwise.
+#[command]
+pub async fn clone_repo(
+    url: String,
+    file_path: String,
+    app: AppHandle,
+    token: Option<String>,
+    options: Option<CloneRequestOptions>,
+    operation_id: Option<String>,
+) -> Result<CloneResult> {
+    run_blocking(move || {
+        let operation = start_operation("clone", operation_id)?;
+        let options = options.unwrap_or_default();
+        let target = Path::new(&file_path);
+        let created = !target.exists();
+        let was_empty = fs::read_dir(target)
+            .map(|mut entries| entries.next().is_none())
+            .unwrap_or(false);
+
+        let progress = CloneProgress {
+            app: app.clone(),
+            operation_id: operation.id().to_string(),
+            last: Arc::new(Mutex::new((String::new(), 0))),
+        };
+
+        match clone_with_options(&url, target, token.clone(), &options, &operation, &progress) {
+            Ok((repo, credential_method)) => {
+                let bare = repo.is_bare();
+
+                // The superproject is usable without its submodules, so a
+                // submodule that can't be updated doesn't undo the clone.
+                let mut submodules = Vec::new();
+                let mut submodule_error = None;
+                if !bare && options.recurse_submodules.unwrap_or(false) {
+                    if let Err(err) = update_submodules(
+                        &repo,
+                        Path::new(""),
+                        (&url, &url),
+                        &token,
+                        &operation,
+                        &progress,
+                        &mut submodules,
+                    ) {
+                        submodule_error = Some(err.to_string());
+                    }
+                }
 
-    match builder.clone(&url, Path::new(&file_path)) {
-        Ok(_) => {
-            save_repos_in_store(file_path, app)?;
-            Ok(())
+                // The workspace needs a working tree, so bare clones aren't opened.
+                if !bare {
+                    save_repos_in_store(file_path.clone(), app)?;
+                }
+                Ok(CloneResult {
+                    status: "cloned".to_string(),
+                    path: file_path,
+                    operation_id: operation.id().to_string(),
+                    bare,
+                    submodules,
+                    credential_method,
+                    submodule_error,
+                })
+            }
+            Err(e) => {
+                if created || was_empty {
+                    if let Err(err) = remove_partial_clone(target, created) {
+                        log::warn!("Failed to clean up partial clone at {}: {}", file_path, err);
+                    }
+                }
+
+                match e {
+                    Error::Cancelled(_) => Ok(CloneResult {
+                        status: "cancelled".to_string(),
+                        path: file_path,
+                        operation_id: operation.id().to_string(),
+                        bare: false,
+                        submodules: Vec::new(),
+                        credential_method: None,
+                        submodule_error: None,
+                    }),
+                    e => Err(e),
+                }
+            }
         }
-        Err(e) => return Err(Error::from(e)),
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_relative_submodule_urls() {
+        assert_eq!(
+            resolve_submodule_url("https://host/owner/repo.git", "../lib.git"),
+            "https://host/owner/lib.git"
+        );
+        assert_eq!(
+            resolve_submodule_url("git@host:owner/repo.git", "../../other/lib.git"),
+            "git@host:other/lib.git"
+        );
+        assert_eq!(
+            resolve_submodule_url("https://host/owner/repo", "https://elsewhere/lib"),
+            "https://elsewhere/lib"
+        );
+    }
+
+    #[test]
+    fn offers_token_only_to_the_superproject_host() {
+        let token = Some("token".to_string());
+        let superproject = "https://github.com/owner/repo";
+
+        assert_eq!(
+            submodule_token(superproject, "https://GitHub.com/owner/lib", &token),
+            token
+        );
+        assert_eq!(
+            submodule_token(superproject, "https://evil.example/lib", &token),
+            None
+        );
+        assert_eq!(
+            submodule_token(superproject, "http://github.com/owner/lib", &token),
+            None
+        );
+        assert_eq!(submodule_token(superproject, "/local/lib", &token), None);
     }
 }
@@ -1,9 +1,12 @@
 use chrono::{DateTime, FixedOffset};
-use git2::{Parents, Sort, Time};
+use git2::{BranchType, Oid, Parents, Repository, Signature, Sort, Time};
 use serde::Serialize;
 use tauri::{command, AppHandle};
 
-use crate::{repo::error::Result, repo::open_repo};
+use crate::{
+    repo::error::{Error, Result},
+    repo::open_repo,
+};
 
 #[derive(Serialize)]
 pub struct CommitInfo {
@@ -16,6 +19,13 @@ pub struct CommitInfo {
     parents: Vec<String>,
 }
 
+#[derive(Serialize)]
+pub struct AmendResult {
+    commit_oid: String,
+    previous_oid: String,
+    already_pushed: bool,
+}
+
 #[command]
 pub fn get_commits(
     app: AppHandle,
@@ -55,7 +65,7 @@ pub fn get_commits(
     Ok(all_commits)
 }
 
-fn convert_date(time: Time) -> String {
+pub(crate) fn convert_date(time: Time) -> String {
     let offset_secs = time.offset_minutes() * 60;
     let offset =
         FixedOffset::east_opt(offset_secs).unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
@@ -114,3 +124,85 @@ pub fn commit(app: AppHandle, repo_path: Option<String>, message: String) -> Res
 
     Ok(commit_id.to_string())
 }
+
+/// Whether `oid` is reachable from the upstream of the checked out branch.
+pub(crate) fn is_on_upstream(repo: &Repository, oid: Oid) -> Result<bool> {
+    let head = repo.head()?;
+    if !head.is_branch() {
+        return Ok(false);
+    }
+
+    let Some(branch_name) = head.shorthand() else {
+        return Ok(false);
+    };
+
+    let branch = repo.find_branch(branch_name, BranchType::Local)?;
+    let upstream_oid = match branch.upstream() {
+        Ok(upstream) => match upstream.get().target() {
+            Some(target) => target,
+            None => return Ok(false),
+        },
+        Err(_) => return Ok(false),
+    };
+
+    Ok(upstream_oid == oid || repo.graph_descendant_of(upstream_oid, oid)?)
+}
+
+#[command]
+pub fn amend_commit(
+    app: AppHandle,
+    repo_path: Option<String>,
+    message: Option<String>,
+    author_name: Option<String>,
+    author_email: Option<String>,
+    allow_pushed: bool,
+) -> Result<AmendResult> {
+    let repo = open_repo(app.clone(), repo_path)?;
+
+    let head_commit = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .map_err(|_| Error::CommitError("There is no commit to amend".to_string()))?;
+
+    let already_pushed = is_on_upstream(&repo, head_commit.id())?;
+    if already_pushed && !allow_pushed {
+        return Err(Error::CommitError(
+            "The last commit has already been pushed to its upstream branch".to_string(),
+        ));
+    }
+
+    let mut index = repo.index()?;
+    let tree_oid = index.write_tree()?;
+    let tree = repo.find_tree(tree_oid)?;
+
+    let original_author = head_commit.author();
+    let author = match (author_name, author_email) {
+        (None, None) => original_author.to_owned(),
+        (name, email) => Signature::new(
+            name.as_deref()
+                .unwrap_or(original_author.name().unwrap_or("Unknown")),
+            email
+                .as_deref()
+                .unwrap_or(original_author.email().unwrap_or("")),
+            &original_author.when(),
+        )?,
+    };
+    let committer = repo.signature()?;
+
+    let message = message.filter(|value| !value.trim().is_empty());
+
+    let commit_oid = head_commit.amend(
+        Some("HEAD"),
+        Some(&author),
+        Some(&committer),
+        None,
+        message.as_deref(),
+        Some(&tree),
+    )?;
+
+    Ok(AmendResult {
+        commit_oid: commit_oid.to_string(),
+        previous_oid: head_commit.id().to_string(),
+        already_pushed,
+    })
+}
@@ -0,0 +1,359 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use git2::{Cred, CredentialType, Error as GitError, ErrorCode, RemoteCallbacks, Repository};
+use parking_lot::Mutex;
+
+use crate::{
+    auth::accounts::{account_credentials, is_session_host},
+    repo::{
+        remote::normalize_token,
+        ssh::{check_host_key, CredentialMethod, RemoteAuth, SshCredentials},
+    },
+};
+
+/// libgit2 asks again after every rejected credential, so a remote that
+/// keeps refusing would otherwise never let the operation fail.
+const MAX_CREDENTIAL_REQUESTS: usize = 10;
+const HELPER_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Clone, Copy, PartialEq)]
+enum CredentialSource {
+    /// Token passed in by the frontend, the github.com session token.
+    Token,
+    CredentialHelper,
+    /// Token of the account saved for the remote's host.
+    Account,
+    SshAgent,
+    SshKey,
+}
+
+impl CredentialSource {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Token => "token",
+            Self::CredentialHelper => "credential_helper",
+            Self::Account => "account",
+            Self::SshAgent => "ssh_agent",
+            Self::SshKey => "ssh_key",
+        }
+    }
+}
+
+/// Username and password over HTTPS are tried in this order. An account
+/// saved for the host is the most specific answer, so it goes first.
+const USERPASS_SOURCES: [CredentialSource; 3] = [
+    CredentialSource::Account,
+    CredentialSource::Token,
+    CredentialSource::CredentialHelper,
+];
+
+#[derive(Clone, PartialEq)]
+struct UserPass {
+    username: String,
+    password: String,
+}
+
+struct ProviderState {
+    auth: RemoteAuth,
+    workdir: Option<PathBuf>,
+    token: Option<String>,
+    ssh: SshCredentials,
+    requests: usize,
+    next_source: usize,
+    offered: Vec<UserPass>,
+    /// What `git credential fill` answered, asked at most once.
+    helper: Option<Option<UserPass>>,
+    current: Option<CredentialSource>,
+}
+
+/// Answers credential requests for every network operation: the account
+/// saved for the remote's host, the given token on github.com, then the
+/// user's `git credential` helpers over HTTPS, and SSH keys otherwise. Each
+/// call to `callbacks` starts over for a new connection; the helper is only
+/// asked once per provider.
+#[derive(Clone)]
+pub(crate) struct CredentialProvider(Arc<Mutex<ProviderState>>);
+
+impl CredentialProvider {
+    pub(crate) fn new(token: Option<String>, auth: RemoteAuth, repo: Option<&Repository>) -> Self {
+        let workdir = repo.map(|repo| repo.workdir().unwrap_or(repo.path()).to_path_buf());
+
+        Self(Arc::new(Mutex::new(ProviderState {
+            ssh: SshCredentials::new(auth.clone()),
+            auth,
+            workdir,
+            token: normalize_token(token),
+            requests: 0,
+            next_source: 0,
+            offered: Vec::new(),
+            helper: None,
+            current: None,
+        })))
+    }
+
+    pub(crate) fn callbacks<'a>(&self) -> RemoteCallbacks<'a> {
+        let url = {
+            let mut state = self.0.lock();
+            state.ssh = SshCredentials::new(state.auth.clone());
+            state.requests = 0;
+            state.next_source = 0;
+            state.offered.clear();
+            state.current = None;
+            state.auth.url.clone()
+        };
+
+        let mut callbacks = RemoteCallbacks::new();
+        let state = self.0.clone();
+        callbacks.credentials(move |_url, username_from_url, allowed_types| {
+            state.lock().next(username_from_url, allowed_types)
+        });
+        callbacks.certificate_check(move |cert, host| check_host_key(cert, host, &url));
+
+        callbacks
+    }
+
+    /// Call once the operation went through. Lets the credential helper
+    /// store what it handed out and returns the method that was accepted,
+    /// or `None` when the remote didn't ask for credentials.
+    pub(crate) fn succeeded(&self) -> Option<String> {
+        let state = self.0.lock();
+        let source = state.current?;
+
+        if source == CredentialSource::CredentialHelper {
+            if let Some(Some(credential)) = &state.helper {
+                run_credential_helper(
+                    "approve",
+                    state.workdir.as_deref(),
+                    &state.auth.url,
+                    Some(credential),
+                );
+            }
+        }
+
+        Some(source.label().to_string())
+    }
+
+    /// Call when the operation failed. If the remote refused what the
+    /// credential helper handed out, the helper is told to forget it.
+    pub(crate) fn failed(&self, err: &GitError) {
+        let mut state = self.0.lock();
+        if state.requests > MAX_CREDENTIAL_REQUESTS || err.code() == ErrorCode::Auth {
+            state.reject_helper();
+        }
+    }
+}
+
+impl ProviderState {
+    /// Tells the credential helper to forget the answer the remote refused,
+    /// if that was the last one offered. It's dropped as well, so it is
+    /// neither offered nor rejected again.
+    fn reject_helper(&mut self) {
+        if self.current != Some(CredentialSource::CredentialHelper) {
+            return;
+        }
+        self.current = None;
+
+        if let Some(Some(credential)) = self.helper.replace(None) {
+            run_credential_helper(
+                "reject",
+                self.workdir.as_deref(),
+                &self.auth.url,
+                Some(&credential),
+            );
+        }
+    }
+
+    fn next(
+        &mut self,
+        username_from_url: Option<&str>,
+        allowed_types: CredentialType,
+    ) -> std::result::Result<Cred, GitError> {
+        self.requests += 1;
+        if self.requests > MAX_CREDENTIAL_REQUESTS {
+            return Err(GitError::from_str(&format!(
+                "Authentication for {} kept failing, giving up",
+                self.auth.url
+            )));
+        }
+
+        if self.ssh.allows_token() && allowed_types.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            return self.next_userpass(username_from_url);
+        }
+
+        if allowed_types.contains(CredentialType::SSH_KEY) {
+            let credential = self.ssh.next(username_from_url)?;
+            self.current = Some(match self.ssh.last_offered() {
+                Some(CredentialMethod::SshAgent) => CredentialSource::SshAgent,
+                _ => CredentialSource::SshKey,
+            });
+            return Ok(credential);
+        }
+
+        if allowed_types.contains(CredentialType::USERNAME) {
+            return Cred::username(&self.ssh.username(username_from_url));
+        }
+
+        Cred::default()
+    }
+
+    fn next_userpass(
+        &mut self,
+        username_from_url: Option<&str>,
+    ) -> std::result::Result<Cred, GitError> {
+        // Being asked again means the last answer was refused.
+        self.reject_helper();
+
+        let token_username = username_from_url.unwrap_or("x-access-token");
+        while let Some(source) = USERPASS_SOURCES.get(self.next_source).copied() {
+            self.next_source += 1;
+
+            let candidate = match source {
+                CredentialSource::Token => self
+                    .token
+                    .clone()
+                    .filter(|_| is_session_host(&self.auth.url))
+                    .map(|password| UserPass {
+                        username: token_username.to_string(),
+                        password,
+                    }),
+                CredentialSource::CredentialHelper => self
+                    .helper
+                    .get_or_insert_with(|| {
+                        credential_fill(self.workdir.as_deref(), &self.auth.url, username_from_url)
+                    })
+                    .clone(),
+                _ => account_credentials(&self.auth.url).map(|(login, password)| UserPass {
+                    username: username_from_url.unwrap_or(&login).to_string(),
+                    password,
+                }),
+            };
+
+            // The same token often sits in an account and a helper.
+            let Some(candidate) = candidate.filter(|value| !self.offered.contains(value)) else {
+                continue;
+            };
+            self.offered.push(candidate.clone());
+            self.current = Some(source);
+
+            return Cred::userpass_plaintext(&candidate.username, &candidate.password);
+        }
+
+        self.current = None;
+        Err(GitError::from_str(&format!(
+            "Authentication failed for {}",
+            self.auth.url
+        )))
+    }
+}
+
+// region:    --- git credential
+
+fn wait_with_timeout(mut child: Child, timeout: Duration) -> Option<std::process::Output> {
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => return child.wait_with_output().ok(),
+            Ok(None) if started.elapsed() < timeout => thread::sleep(Duration::from_millis(50)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+}
+
+/// Runs `git credential <action>`, which goes through whatever helpers the
+/// user configured. Prompts are disabled: with no helper answering, `fill`
+/// just fails. Returns stdout, or `None` if git is missing or failed.
+fn run_credential_helper(
+    action: &str,
+    workdir: Option<&Path>,
+    url: &str,
+    credential: Option<&UserPass>,
+) -> Option<String> {
+    let mut input = format!("url={}\n", url);
+    if let Some(credential) = credential {
+        input.push_str(&format!("username={}\n", credential.username));
+        if !credential.password.is_empty() {
+            input.push_str(&format!("password={}\n", credential.password));
+        }
+    }
+    input.push('\n');
+
+    let mut command = Command::new("git");
+    command
+        .args(["credential", action])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        // An empty askpass stops git from falling back to SSH_ASKPASS.
+        .env("GIT_ASKPASS", "")
+        // Nor may Git Credential Manager open a window, e.g. during auto-fetch.
+        .env("GCM_INTERACTIVE", "never")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    if let Some(workdir) = workdir {
+        command.current_dir(workdir);
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+
+        // Keeps a console window from flashing up on every call.
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(err) => {
+            log::warn!("Could not run git credential {}: {}", action, err);
+            return None;
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(input.as_bytes());
+    }
+
+    let output = wait_with_timeout(child, HELPER_TIMEOUT)?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn credential_fill(workdir: Option<&Path>, url: &str, username: Option<&str>) -> Option<UserPass> {
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return None;
+    }
+
+    let request = username.map(|username| UserPass {
+        username: username.to_string(),
+        password: String::new(),
+    });
+    let output = run_credential_helper("fill", workdir, url, request.as_ref())?;
+
+    let mut username = None;
+    let mut password = None;
+    for line in output.lines() {
+        match line.split_once('=') {
+            Some(("username", value)) => username = Some(value.to_string()),
+            Some(("password", value)) => password = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    Some(UserPass {
+        username: username.unwrap_or_default(),
+        password: password.filter(|value| !value.is_empty())?,
+    })
+}
+
+// endregion: --- git credential
@@ -0,0 +1,267 @@
+use git2::{Delta, Diff, DiffFindOptions, DiffOptions, FileMode, Oid, Patch, Repository};
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle};
+
+use crate::repo::{error::Result, open_repo};
+
+const DEFAULT_CONTEXT_LINES: u32 = 3;
+
+#[derive(Clone, Default, Deserialize)]
+pub struct DiffRequestOptions {
+    pub context_lines: Option<u32>,
+    pub ignore_whitespace: Option<bool>,
+    /// Files above this many bytes are reported as binary, without hunks.
+    pub max_file_size: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct DiffLine {
+    origin: String,
+    old_lineno: Option<u32>,
+    new_lineno: Option<u32>,
+    content: String,
+}
+
+#[derive(Serialize)]
+pub struct DiffHunk {
+    header: String,
+    old_start: u32,
+    old_lines: u32,
+    new_start: u32,
+    new_lines: u32,
+    lines: Vec<DiffLine>,
+}
+
+#[derive(Serialize)]
+pub struct FileDiff {
+    status: String,
+    old_path: Option<String>,
+    new_path: Option<String>,
+    old_mode: Option<String>,
+    new_mode: Option<String>,
+    mode_changed: bool,
+    is_binary: bool,
+    additions: usize,
+    deletions: usize,
+    hunks: Vec<DiffHunk>,
+}
+
+#[derive(Serialize)]
+pub struct DiffStats {
+    files_changed: usize,
+    additions: usize,
+    deletions: usize,
+}
+
+#[derive(Serialize)]
+pub struct UnifiedDiff {
+    files: Vec<FileDiff>,
+    stats: DiffStats,
+}
+
+fn build_diff_options(options: &DiffRequestOptions) -> DiffOptions {
+    let mut opts = DiffOptions::new();
+    opts.context_lines(options.context_lines.unwrap_or(DEFAULT_CONTEXT_LINES))
+        .ignore_whitespace(options.ignore_whitespace.unwrap_or(false))
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+
+    if let Some(max_file_size) = options.max_file_size {
+        opts.max_size(max_file_size);
+    }
+
+    opts
+}
+
+fn status_label(status: Delta) -> &'static str {
+    match status {
+        Delta::Added | Delta::Untracked => "added",
+        Delta::Deleted => "deleted",
+        Delta::Renamed => "renamed",
+        Delta::Copied => "copied",
+        Delta::Typechange => "typechange",
+        Delta::Conflicted => "conflicted",
+        Delta::Unmodified => "unmodified",
+        _ => "modified",
+    }
+}
+
+fn mode_label(mode: FileMode) -> Option<String> {
+    match mode {
+        FileMode::Unreadable => None,
+        mode => Some(format!("{:o}", u32::from(mode))),
+    }
+}
+
+fn origin_label(origin: char) -> &'static str {
+    match origin {
+        '+' => "addition",
+        '-' => "deletion",
+        '>' => "addition_eofnl",
+        '<' => "deletion_eofnl",
+        '=' => "context_eofnl",
+        _ => "context",
+    }
+}
+
+fn file_diff_from_patch(patch: &Patch) -> Result<FileDiff> {
+    let delta = patch.delta();
+    let old_path = delta
+        .old_file()
+        .path()
+        .map(|path| path.to_string_lossy().to_string());
+    let new_path = delta
+        .new_file()
+        .path()
+        .map(|path| path.to_string_lossy().to_string());
+    let old_mode = mode_label(delta.old_file().mode());
+    let new_mode = mode_label(delta.new_file().mode());
+    let is_binary =
+        delta.flags().is_binary() || delta.old_file().is_binary() || delta.new_file().is_binary();
+
+    let mut hunks: Vec<DiffHunk> = Vec::new();
+    if !is_binary {
+        for hunk_idx in 0..patch.num_hunks() {
+            let (hunk, line_count) = patch.hunk(hunk_idx)?;
+            let mut lines: Vec<DiffLine> = Vec::new();
+
+            for line_idx in 0..line_count {
+                let line = patch.line_in_hunk(hunk_idx, line_idx)?;
+                lines.push(DiffLine {
+                    origin: origin_label(line.origin()).to_string(),
+                    old_lineno: line.old_lineno(),
+                    new_lineno: line.new_lineno(),
+                    content: String::from_utf8_lossy(line.content()).to_string(),
+                });
+            }
+
+            hunks.push(DiffHunk {
+                header: String::from_utf8_lossy(hunk.header())
+                    .trim_end()
+                    .to_string(),
+                old_start: hunk.old_start(),
+                old_lines: hunk.old_lines(),
+                new_start: hunk.new_start(),
+                new_lines: hunk.new_lines(),
+                lines,
+            });
+        }
+    }
+
+    let (_, additions, deletions) = patch.line_stats()?;
+
+    Ok(FileDiff {
+        status: status_label(delta.status()).to_string(),
+        mode_changed: old_mode.is_some() && new_mode.is_some() && old_mode != new_mode,
+        old_path,
+        new_path,
+        old_mode,
+        new_mode,
+        is_binary,
+        additions,
+        deletions,
+        hunks,
+    })
+}
+
+/// Detects renames and copies, then builds the patches of the deltas that
+/// touch `file_path` (all of them when it is `None`). Filtering happens after
+/// rename detection so a renamed file still shows up under either name.
+fn unified_diff(mut diff: Diff, file_path: Option<&str>) -> Result<UnifiedDiff> {
+    let mut find_options = DiffFindOptions::new();
+    find_options
+        .renames(true)
+        .copies(true)
+        .renames_from_rewrites(true)
+        .for_untracked(true);
+    diff.find_similar(Some(&mut find_options))?;
+
+    let mut files: Vec<FileDiff> = Vec::new();
+    for (delta_idx, delta) in diff.deltas().enumerate() {
+        if let Some(file_path) = file_path {
+            let matches = [delta.old_file().path(), delta.new_file().path()]
+                .into_iter()
+                .flatten()
+                .any(|path| path.to_string_lossy() == file_path);
+            if !matches {
+                continue;
+            }
+        }
+
+        if let Some(patch) = Patch::from_diff(&diff, delta_idx)? {
+            files.push(file_diff_from_patch(&patch)?);
+        }
+    }
+
+    let stats = DiffStats {
+        files_changed: files.len(),
+        additions: files.iter().map(|file| file.additions).sum(),
+        deletions: files.iter().map(|file| file.deletions).sum(),
+    };
+
+    Ok(UnifiedDiff { files, stats })
+}
+
+fn head_tree(repo: &Repository) -> Result<Option<git2::Tree<'_>>> {
+    match repo.head() {
+        Ok(head) => Ok(Some(head.peel_to_tree()?)),
+        Err(err)
+            if matches!(
+                err.code(),
+                git2::ErrorCode::UnbornBranch | git2::ErrorCode::NotFound
+            ) =>
+        {
+            Ok(None)
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Diff of uncommitted changes: HEAD -> index when `staged`, index -> working
+/// tree otherwise.
+#[command]
+pub fn get_working_diff(
+    app: AppHandle,
+    staged: bool,
+    file_path: Option<String>,
+    options: Option<DiffRequestOptions>,
+    repo_path: Option<String>,
+) -> Result<UnifiedDiff> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    let mut opts = build_diff_options(&options.unwrap_or_default());
+
+    let diff = if staged {
+        let tree = head_tree(&repo)?;
+        repo.diff_tree_to_index(tree.as_ref(), None, Some(&mut opts))?
+    } else {
+        repo.diff_index_to_workdir(None, Some(&mut opts))?
+    };
+
+    unified_diff(diff, file_path.as_deref())
+}
+
+/// Diff of a commit against its first parent.
+#[command]
+pub fn get_commit_diff(
+    app: AppHandle,
+    commit_id: String,
+    file_path: Option<String>,
+    options: Option<DiffRequestOptions>,
+    repo_path: Option<String>,
+) -> Result<UnifiedDiff> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    let mut opts = build_diff_options(&options.unwrap_or_default());
+
+    let commit = repo.find_commit(Oid::from_str(&commit_id)?)?;
+    let tree = commit.tree()?;
+    let parent_tree = if commit.parent_count() > 0 {
+        Some(commit.parent(0)?.tree()?)
+    } else {
+        None
+    };
+
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts))?;
+
+    unified_diff(diff, file_path.as_deref())
+}
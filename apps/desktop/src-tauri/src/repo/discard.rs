@@ -0,0 +1,448 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use git2::{
+    build::CheckoutBuilder, Delta, ErrorCode, Index, IndexEntry, IndexTime, Oid, Repository,
+    ResetType, Status, StatusOptions,
+};
+use serde::Serialize;
+use tauri::{command, AppHandle};
+
+use crate::repo::{
+    error::{Error, Result},
+    open_repo,
+    staging::{
+        apply_selection, load_file_patch, pick_mode, read_blob_or_empty, selects_all, selects_any,
+        to_selection, HunkSelection,
+    },
+};
+
+#[derive(Clone, Serialize)]
+pub struct DiscardResult {
+    discarded_paths: Vec<String>,
+    skipped_paths: Vec<String>,
+    safety_stash_oid: Option<String>,
+}
+
+fn describe_head(repo: &Repository) -> String {
+    match repo.head() {
+        Ok(head) => head.shorthand().unwrap_or("HEAD").to_string(),
+        Err(_) => "HEAD".to_string(),
+    }
+}
+
+fn entry_for(path: &str, mode: u32, id: Oid, file_size: u32) -> IndexEntry {
+    IndexEntry {
+        ctime: IndexTime::new(0, 0),
+        mtime: IndexTime::new(0, 0),
+        dev: 0,
+        ino: 0,
+        mode,
+        uid: 0,
+        gid: 0,
+        file_size,
+        id,
+        flags: 0,
+        flags_extended: 0,
+        path: path.as_bytes().to_vec(),
+    }
+}
+
+/// The working tree version of `path` as git would store it: run through
+/// the configured filters, and the link target for a symlink. `None` when
+/// there is no file at `path`.
+fn worktree_entry(
+    repo: &Repository,
+    workdir: &Path,
+    path: &str,
+    index_mode: Option<u32>,
+) -> Result<Option<IndexEntry>> {
+    let full_path = workdir.join(path);
+    let metadata = match fs::symlink_metadata(&full_path) {
+        Ok(metadata) if !metadata.is_dir() => metadata,
+        Ok(_) => return Ok(None),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut mode = match index_mode {
+        Some(mode) if mode != 0o120000 => mode,
+        _ => 0o100644,
+    };
+    if metadata.file_type().is_symlink() {
+        mode = 0o120000;
+    } else {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            if metadata.permissions().mode() & 0o111 != 0 {
+                mode = 0o100755;
+            }
+        }
+    }
+
+    let blob_oid = if mode == 0o120000 {
+        let target = fs::read_link(&full_path)?;
+        repo.blob(target.to_string_lossy().as_bytes())?
+    } else {
+        // A hint path makes libgit2 apply the filters configured for it.
+        let mut writer = repo.blob_writer(Some(Path::new(path)))?;
+        writer.write_all(&fs::read(&full_path)?)?;
+        writer.commit()?
+    };
+    Ok(Some(entry_for(path, mode, blob_oid, metadata.len() as u32)))
+}
+
+/// Writes `content` to `path` through a checkout, so the filters turn it back
+/// into its working tree form and a symlink comes out as a link.
+fn write_worktree_file(repo: &Repository, path: &str, mode: u32, content: &[u8]) -> Result<()> {
+    let blob_oid = repo.blob(content)?;
+    let mut index = Index::new()?;
+    index.add(&entry_for(path, mode, blob_oid, content.len() as u32))?;
+
+    let mut checkout_builder = CheckoutBuilder::new();
+    checkout_builder
+        .force()
+        .update_index(false)
+        .disable_pathspec_match(true)
+        .path(path);
+    repo.checkout_index(Some(&mut index), Some(&mut checkout_builder))?;
+    Ok(())
+}
+
+/// Records the working tree content of `paths` as a regular stash entry
+/// before it gets thrown away, so a discard can be undone from the stash list.
+/// Returns `None` when there is nothing to protect. Like `git stash`, refuses
+/// on an unborn HEAD, since a stash needs a commit to sit on.
+pub(crate) fn save_safety_stash(
+    repo: &Repository,
+    paths: &[String],
+    message: &str,
+) -> Result<Option<Oid>> {
+    if paths.is_empty() {
+        return Ok(None);
+    }
+
+    let head_commit = match repo.head() {
+        Ok(head) => head.peel_to_commit()?,
+        Err(err) if matches!(err.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => {
+            return Err(Error::StashError(
+                "Nothing is committed yet, so the changes couldn't be kept in a safety stash. \
+                 Make a first commit before discarding."
+                    .to_string(),
+            ))
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    let workdir = repo.workdir().ok_or_else(|| {
+        Error::RepoOpeningError("Repository working directory unavailable".to_string())
+    })?;
+
+    let signature = repo.signature()?;
+    let branch = describe_head(repo);
+    let head_summary = format!(
+        "{} {}",
+        &head_commit.id().to_string()[..7],
+        head_commit.summary().unwrap_or("")
+    );
+
+    let mut repo_index = repo.index()?;
+    let index_tree = repo.find_tree(repo_index.write_tree()?)?;
+    let index_commit_oid = repo.commit(
+        None,
+        &signature,
+        &signature,
+        &format!("index on {}: {}", branch, head_summary),
+        &index_tree,
+        &[&head_commit],
+    )?;
+    let index_commit = repo.find_commit(index_commit_oid)?;
+
+    let mut snapshot = Index::new()?;
+    snapshot.read_tree(&index_tree)?;
+
+    for path in paths {
+        let index_mode = snapshot
+            .get_path(Path::new(path), 0)
+            .map(|entry| entry.mode);
+        match worktree_entry(repo, workdir, path, index_mode)? {
+            Some(entry) => snapshot.add(&entry)?,
+            None if index_mode.is_some() => snapshot.remove_path(Path::new(path))?,
+            None => {}
+        }
+    }
+
+    let worktree_tree = repo.find_tree(snapshot.write_tree_to(repo)?)?;
+    let stash_message = format!("On {}: {}", branch, message);
+    let stash_oid = repo.commit(
+        None,
+        &signature,
+        &signature,
+        &stash_message,
+        &worktree_tree,
+        &[&head_commit, &index_commit],
+    )?;
+
+    repo.reference("refs/stash", stash_oid, true, &stash_message)?;
+
+    // libgit2 only writes a reflog for refs/stash once one exists, and the
+    // stash list is read from that reflog.
+    let mut reflog = repo.reflog("refs/stash")?;
+    let already_logged = reflog
+        .get(0)
+        .map(|entry| entry.id_new() == stash_oid)
+        .unwrap_or(false);
+    if !already_logged {
+        reflog.append(stash_oid, &signature, Some(&stash_message))?;
+        reflog.write()?;
+    }
+
+    Ok(Some(stash_oid))
+}
+
+fn changed_paths(repo: &Repository, paths: Option<&[String]>) -> Result<Vec<(String, Status)>> {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true).recurse_untracked_dirs(true);
+    if let Some(paths) = paths {
+        opts.disable_pathspec_match(true);
+        for path in paths {
+            opts.pathspec(path);
+        }
+    }
+
+    let statuses = repo.statuses(Some(&mut opts))?;
+    let mut changes: Vec<(String, Status)> = Vec::new();
+    for entry in statuses.iter() {
+        if let Some(path) = entry.path() {
+            changes.push((path.to_string(), entry.status()));
+        }
+    }
+
+    Ok(changes)
+}
+
+fn is_worktree_change(status: Status) -> bool {
+    status.intersects(
+        Status::WT_NEW
+            | Status::WT_MODIFIED
+            | Status::WT_DELETED
+            | Status::WT_TYPECHANGE
+            | Status::WT_RENAMED,
+    )
+}
+
+#[command]
+pub fn discard_file_changes(
+    app: AppHandle,
+    file_paths: Vec<String>,
+    include_staged: bool,
+    remove_untracked: bool,
+    repo_path: Option<String>,
+) -> Result<DiscardResult> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| {
+            Error::RepoOpeningError("Repository working directory unavailable".to_string())
+        })?
+        .to_path_buf();
+
+    let changes = changed_paths(&repo, Some(&file_paths))?;
+    let mut to_checkout: Vec<String> = Vec::new();
+    let mut to_remove: Vec<String> = Vec::new();
+    let mut skipped_paths: Vec<String> = Vec::new();
+
+    for (path, status) in changes {
+        if status.contains(Status::WT_NEW) && !status.contains(Status::INDEX_NEW) {
+            if remove_untracked {
+                to_remove.push(path);
+            } else {
+                skipped_paths.push(path);
+            }
+        } else if is_worktree_change(status) || include_staged {
+            to_checkout.push(path);
+        }
+    }
+
+    let mut protected: Vec<String> = to_checkout.clone();
+    protected.extend(to_remove.iter().cloned());
+    let safety_stash = save_safety_stash(&repo, &protected, "discarded changes")?;
+
+    if include_staged && !to_checkout.is_empty() {
+        let head_commit = match repo.head() {
+            Ok(head) => Some(head.peel_to_commit()?),
+            Err(_) => None,
+        };
+        let head_object = head_commit.as_ref().map(|commit| commit.as_object());
+        repo.reset_default(head_object, to_checkout.iter().map(|path| path.as_str()))?;
+
+        // Files that only existed in the index are untracked now, and only
+        // deleted along with the other untracked files.
+        let mut index = repo.index()?;
+        index.read(true)?;
+        to_checkout.retain(|path| {
+            if index.get_path(Path::new(path), 0).is_some() {
+                true
+            } else if remove_untracked {
+                to_remove.push(path.clone());
+                false
+            } else {
+                skipped_paths.push(path.clone());
+                false
+            }
+        });
+    }
+
+    if !to_checkout.is_empty() {
+        let mut checkout_builder = CheckoutBuilder::new();
+        checkout_builder.force().disable_pathspec_match(true);
+        for path in &to_checkout {
+            checkout_builder.path(path.as_str());
+        }
+        repo.checkout_index(None, Some(&mut checkout_builder))?;
+    }
+
+    for path in &to_remove {
+        let full_path = workdir.join(path);
+        if full_path.exists() {
+            fs::remove_file(full_path)?;
+        }
+    }
+
+    let mut discarded_paths = to_checkout;
+    discarded_paths.extend(to_remove);
+
+    Ok(DiscardResult {
+        discarded_paths,
+        skipped_paths,
+        safety_stash_oid: safety_stash.map(|oid| oid.to_string()),
+    })
+}
+
+#[command]
+pub fn discard_hunks(
+    app: AppHandle,
+    file_path: String,
+    selection: Vec<HunkSelection>,
+    repo_path: Option<String>,
+) -> Result<DiscardResult> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    let index = repo.index()?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| {
+            Error::RepoOpeningError("Repository working directory unavailable".to_string())
+        })?
+        .to_path_buf();
+
+    let patch = load_file_patch(&repo, &index, &file_path, false)?
+        .ok_or_else(|| Error::StagingError(format!("No changes found for {}", file_path)))?;
+
+    if patch.is_binary {
+        return Err(Error::StagingError(format!(
+            "Cannot select hunks in binary file {}",
+            file_path
+        )));
+    }
+
+    let selection = to_selection(&selection);
+    if !selects_any(&patch, &selection) {
+        return Ok(DiscardResult {
+            discarded_paths: Vec::new(),
+            skipped_paths: Vec::new(),
+            safety_stash_oid: None,
+        });
+    }
+    let safety_stash =
+        save_safety_stash(&repo, std::slice::from_ref(&file_path), "discarded hunks")?;
+
+    let full_path = workdir.join(&file_path);
+    let is_new = matches!(patch.status, Delta::Untracked | Delta::Added);
+
+    if is_new && selects_all(&patch, &selection, '+') {
+        fs::remove_file(&full_path)?;
+    } else {
+        // The patch was made from the filtered file, so that's what the
+        // selection applies to.
+        let base = match worktree_entry(&repo, &workdir, &file_path, None)? {
+            Some(entry) => read_blob_or_empty(&repo, entry.id)?,
+            None => Vec::new(),
+        };
+        let content = apply_selection(&base, &patch, &selection, true);
+        let mode = pick_mode(patch.new_mode, patch.old_mode);
+        write_worktree_file(&repo, &file_path, mode, &content)?;
+    }
+
+    Ok(DiscardResult {
+        discarded_paths: vec![file_path],
+        skipped_paths: Vec::new(),
+        safety_stash_oid: safety_stash.map(|oid| oid.to_string()),
+    })
+}
+
+#[command]
+pub fn discard_all_changes(
+    app: AppHandle,
+    remove_untracked: bool,
+    repo_path: Option<String>,
+) -> Result<DiscardResult> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| {
+            Error::RepoOpeningError("Repository working directory unavailable".to_string())
+        })?
+        .to_path_buf();
+
+    let mut protected: Vec<String> = Vec::new();
+    let mut untracked: Vec<String> = Vec::new();
+    let mut skipped_paths: Vec<String> = Vec::new();
+    for (path, status) in changed_paths(&repo, None)? {
+        if status.contains(Status::WT_NEW) {
+            if remove_untracked {
+                untracked.push(path.clone());
+                protected.push(path);
+            } else {
+                skipped_paths.push(path);
+            }
+        } else if !status.contains(Status::IGNORED) {
+            protected.push(path);
+        }
+    }
+
+    if protected.is_empty() {
+        return Ok(DiscardResult {
+            discarded_paths: protected,
+            skipped_paths,
+            safety_stash_oid: None,
+        });
+    }
+    let safety_stash = save_safety_stash(&repo, &protected, "discarded all changes")?;
+
+    let head_commit = repo.head()?.peel_to_commit()?;
+    let mut checkout_builder = CheckoutBuilder::new();
+    checkout_builder.force();
+    repo.reset(
+        head_commit.as_object(),
+        ResetType::Hard,
+        Some(&mut checkout_builder),
+    )?;
+
+    for path in &untracked {
+        let full_path = workdir.join(path);
+        if full_path.exists() {
+            fs::remove_file(full_path)?;
+        }
+    }
+
+    Ok(DiscardResult {
+        discarded_paths: protected,
+        skipped_paths,
+        safety_stash_oid: safety_stash.map(|oid| oid.to_string()),
+    })
+}
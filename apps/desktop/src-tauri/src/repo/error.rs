@@ -10,8 +10,22 @@ pub enum Error {
     StoreError(String),
     SerdeJson(serde_json::Error),
     Git(Arc<git2::Error>),
+    Io(Arc<std::io::Error>),
+    Tauri(Arc<tauri::Error>),
     RepoOpeningError(String),
     RepoToDiffError(String),
+    StagingError(String),
+    StashError(String),
+    CommitError(String),
+    SequencerError(String),
+    ResetError(String),
+    RebaseError(String),
+    MergeError(String),
+    BranchError(String),
+    RemoteError(String),
+    OperationError(String),
+    CredentialError(String),
+    Cancelled(String),
 }
 
 impl Serialize for Error {
@@ -29,6 +43,8 @@ crate::impl_froms! {
     TauriPluginStore(tauri_plugin_store::Error, arc),
     SerdeJson(serde_json::Error),
     Git(git2::Error, arc),
+    Io(std::io::Error, arc),
+    Tauri(tauri::Error, arc),
 }
 
 // endregion: --- Froms
@@ -41,8 +57,22 @@ impl core::fmt::Display for Error {
             Self::StoreError(e) => write!(fmt, "{e}"),
             Self::SerdeJson(e) => write!(fmt, "{e}"),
             Self::Git(e) => write!(fmt, "{e}"),
+            Self::Io(e) => write!(fmt, "{e}"),
+            Self::Tauri(e) => write!(fmt, "{e}"),
             Self::RepoOpeningError(e) => write!(fmt, "{e}"),
             Self::RepoToDiffError(e) => write!(fmt, "{e}"),
+            Self::StagingError(e) => write!(fmt, "{e}"),
+            Self::StashError(e) => write!(fmt, "{e}"),
+            Self::CommitError(e) => write!(fmt, "{e}"),
+            Self::SequencerError(e) => write!(fmt, "{e}"),
+            Self::ResetError(e) => write!(fmt, "{e}"),
+            Self::RebaseError(e) => write!(fmt, "{e}"),
+            Self::MergeError(e) => write!(fmt, "{e}"),
+            Self::BranchError(e) => write!(fmt, "{e}"),
+            Self::RemoteError(e) => write!(fmt, "{e}"),
+            Self::OperationError(e) => write!(fmt, "{e}"),
+            Self::CredentialError(e) => write!(fmt, "{e}"),
+            Self::Cancelled(id) => write!(fmt, "Operation {id} was cancelled"),
         }
     }
 }
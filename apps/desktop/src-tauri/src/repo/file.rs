@@ -49,21 +49,34 @@ pub fn get_file_diff_by_commit(
         None
     };
 
-    let new_content = get_file_content_from_tree(&repo, &tree, &file_path);
-
-    let old_content = if let Some(pt) = parent_tree {
-        get_file_content_from_tree(&repo, &pt, &file_path)
-    } else {
-        String::new()
-    };
+    Ok(diff_state_from_trees(
+        &repo,
+        parent_tree.as_ref(),
+        Some(&tree),
+        &file_path,
+    ))
+}
 
-    Ok(DiffState {
+pub(crate) fn diff_state_from_trees(
+    repo: &Repository,
+    old_tree: Option<&Tree>,
+    new_tree: Option<&Tree>,
+    path: &str,
+) -> DiffState {
+    let new_content = new_tree
+        .map(|tree| get_file_content_from_tree(repo, tree, path))
+        .unwrap_or_default();
+    let old_content = old_tree
+        .map(|tree| get_file_content_from_tree(repo, tree, path))
+        .unwrap_or_default();
+
+    DiffState {
         new_content,
         old_content,
-    })
+    }
 }
 
-fn get_file_content_from_tree(repo: &Repository, tree: &Tree, path: &str) -> String {
+pub(crate) fn get_file_content_from_tree(repo: &Repository, tree: &Tree, path: &str) -> String {
     match tree.get_path(Path::new(path)) {
         Ok(entry) => {
             if let Ok(object) = entry.to_object(repo) {
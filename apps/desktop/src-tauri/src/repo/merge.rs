@@ -0,0 +1,366 @@
+use std::{collections::BTreeSet, fs, path::Path};
+
+use git2::{build::CheckoutBuilder, Commit, IndexEntry, Oid, Repository, RepositoryState};
+use serde::Serialize;
+use tauri::{command, AppHandle};
+
+use crate::repo::{
+    error::{Error, Result},
+    open_repo,
+    status::{conflict_entries, ConflictEntry},
+};
+
+/// Stage bits of `IndexEntry::flags`.
+const INDEX_ENTRY_STAGE_MASK: u16 = 0x3000;
+
+#[derive(Clone, Serialize)]
+pub struct MergeState {
+    in_progress: bool,
+    merge_heads: Vec<String>,
+    message: Option<String>,
+    conflicts: Vec<ConflictEntry>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ConflictVersion {
+    oid: String,
+    content: String,
+    is_binary: bool,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ConflictVersions {
+    path: String,
+    ancestor: Option<ConflictVersion>,
+    ours: Option<ConflictVersion>,
+    theirs: Option<ConflictVersion>,
+    working: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct MergeCommitResult {
+    commit_oid: String,
+    parent_oids: Vec<String>,
+}
+
+fn ensure_merge_in_progress(repo: &Repository) -> Result<()> {
+    if repo.state() != RepositoryState::Merge {
+        return Err(Error::MergeError("No merge is in progress".to_string()));
+    }
+    Ok(())
+}
+
+fn merge_heads(repo: &mut Repository) -> Result<Vec<Oid>> {
+    let mut heads: Vec<Oid> = Vec::new();
+    if repo.state() == RepositoryState::Merge {
+        repo.mergehead_foreach(|oid| {
+            heads.push(*oid);
+            true
+        })?;
+    }
+    Ok(heads)
+}
+
+fn conflict_version(
+    repo: &Repository,
+    entry: Option<&IndexEntry>,
+) -> Result<Option<ConflictVersion>> {
+    let entry = match entry {
+        Some(entry) => entry,
+        None => return Ok(None),
+    };
+
+    let blob = repo.find_blob(entry.id)?;
+    let is_binary = blob.is_binary();
+    Ok(Some(ConflictVersion {
+        oid: entry.id.to_string(),
+        content: if is_binary {
+            String::new()
+        } else {
+            String::from_utf8_lossy(blob.content()).to_string()
+        },
+        is_binary,
+    }))
+}
+
+/// MERGE_MSG lists the conflicted paths as `#` comments, which git drops.
+fn strip_comments(message: &str) -> String {
+    let lines: Vec<&str> = message
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect();
+    format!("{}\n", lines.join("\n").trim_end())
+}
+
+fn workdir_path(repo: &Repository, file_path: &str) -> Result<std::path::PathBuf> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| Error::MergeError("Repository has no working directory".to_string()))?;
+    Ok(workdir.join(file_path))
+}
+
+/// Clears the way for checking out `file_path`. Files the merge added have to
+/// go anyway, and checkout won't turn a symlink back into a regular file.
+fn remove_worktree_file(repo: &Repository, file_path: &str) -> Result<()> {
+    let path = workdir_path(repo, file_path)?;
+    if fs::symlink_metadata(&path).is_ok_and(|metadata| !metadata.is_dir()) {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Stages the working copy of `file_path`, which drops its conflict entries.
+/// A file deleted from the working tree is resolved as a deletion.
+fn stage_resolution(repo: &Repository, file_path: &str) -> Result<()> {
+    let mut index = repo.index()?;
+    if workdir_path(repo, file_path)?.exists() {
+        index.add_path(Path::new(file_path))?;
+    } else {
+        index.remove_path(Path::new(file_path))?;
+    }
+    index.write()?;
+    Ok(())
+}
+
+#[command]
+pub fn get_merge_state(app: AppHandle, repo_path: Option<String>) -> Result<MergeState> {
+    let mut repo = open_repo(app.clone(), repo_path)?;
+
+    let heads = merge_heads(&mut repo)?;
+    let message = if repo.state() == RepositoryState::Merge {
+        repo.message().ok()
+    } else {
+        None
+    };
+
+    Ok(MergeState {
+        in_progress: repo.state() == RepositoryState::Merge,
+        merge_heads: heads.iter().map(|oid| oid.to_string()).collect(),
+        message,
+        conflicts: conflict_entries(&repo.index()?)?,
+    })
+}
+
+#[command]
+pub fn list_conflicts(app: AppHandle, repo_path: Option<String>) -> Result<Vec<ConflictEntry>> {
+    let repo = open_repo(app.clone(), repo_path)?;
+
+    conflict_entries(&repo.index()?)
+}
+
+#[command]
+pub fn get_conflict_versions(
+    app: AppHandle,
+    file_path: String,
+    repo_path: Option<String>,
+) -> Result<ConflictVersions> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    let index = repo.index()?;
+
+    let conflict = index
+        .conflict_get(Path::new(&file_path))
+        .map_err(|_| Error::MergeError(format!("{} is not conflicted", file_path)))?;
+
+    let working_path = workdir_path(&repo, &file_path)?;
+    let working = if working_path.is_file() {
+        Some(String::from_utf8_lossy(&fs::read(working_path)?).to_string())
+    } else {
+        None
+    };
+
+    Ok(ConflictVersions {
+        ancestor: conflict_version(&repo, conflict.ancestor.as_ref())?,
+        ours: conflict_version(&repo, conflict.our.as_ref())?,
+        theirs: conflict_version(&repo, conflict.their.as_ref())?,
+        working,
+        path: file_path,
+    })
+}
+
+/// Writes the resolved content to the working tree, and stages it right away
+/// when `mark_resolved` is set.
+#[command]
+pub fn write_conflict_resolution(
+    app: AppHandle,
+    file_path: String,
+    content: String,
+    mark_resolved: bool,
+    repo_path: Option<String>,
+) -> Result<()> {
+    let repo = open_repo(app.clone(), repo_path)?;
+
+    let path = workdir_path(&repo, &file_path)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+
+    if mark_resolved {
+        stage_resolution(&repo, &file_path)?;
+    }
+
+    Ok(())
+}
+
+/// Resolves a conflict by taking one side wholesale, mode included. If that
+/// side deleted the file, the resolution is the deletion.
+#[command]
+pub fn resolve_conflict_with_side(
+    app: AppHandle,
+    file_path: String,
+    side: String,
+    repo_path: Option<String>,
+) -> Result<()> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    let mut index = repo.index()?;
+
+    let conflict = index
+        .conflict_get(Path::new(&file_path))
+        .map_err(|_| Error::MergeError(format!("{} is not conflicted", file_path)))?;
+
+    let entry = match side.as_str() {
+        "ours" => conflict.our,
+        "theirs" => conflict.their,
+        other => {
+            return Err(Error::MergeError(format!(
+                "Unknown conflict side: {}",
+                other
+            )))
+        }
+    };
+
+    index.conflict_remove(Path::new(&file_path))?;
+    match entry {
+        Some(mut entry) => {
+            // Staged as a regular entry, then checked out from the index so
+            // the executable bit or symlink comes back with the content. The
+            // working file stays put: checkout skips a missing path whose
+            // type differs from HEAD.
+            entry.flags &= !INDEX_ENTRY_STAGE_MASK;
+            index.add(&entry)?;
+            let mut checkout_builder = CheckoutBuilder::new();
+            checkout_builder
+                .force()
+                .recreate_missing(true)
+                .disable_pathspec_match(true)
+                .path(&file_path);
+            repo.checkout_index(Some(&mut index), Some(&mut checkout_builder))?;
+        }
+        None => remove_worktree_file(&repo, &file_path)?,
+    }
+
+    index.write()?;
+    Ok(())
+}
+
+#[command]
+pub fn mark_conflict_resolved(
+    app: AppHandle,
+    file_path: String,
+    repo_path: Option<String>,
+) -> Result<()> {
+    let repo = open_repo(app.clone(), repo_path)?;
+
+    stage_resolution(&repo, &file_path)
+}
+
+#[command]
+pub fn conclude_merge(
+    app: AppHandle,
+    message: Option<String>,
+    repo_path: Option<String>,
+) -> Result<MergeCommitResult> {
+    let mut repo = open_repo(app.clone(), repo_path)?;
+    ensure_merge_in_progress(&repo)?;
+
+    let mut index = repo.index()?;
+    if index.has_conflicts() {
+        return Err(Error::MergeError(
+            "Resolve all conflicts before concluding the merge".to_string(),
+        ));
+    }
+
+    let message = message
+        .filter(|value| !value.trim().is_empty())
+        .or_else(|| repo.message().ok().map(|value| strip_comments(&value)))
+        .ok_or_else(|| Error::MergeError("A merge commit message is required".to_string()))?;
+
+    let heads = merge_heads(&mut repo)?;
+    let head_commit = repo.head()?.peel_to_commit()?;
+    let mut parents: Vec<Commit> = vec![head_commit];
+    for oid in heads {
+        parents.push(repo.find_commit(oid)?);
+    }
+    let parent_refs: Vec<&Commit> = parents.iter().collect();
+
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let signature = repo.signature()?;
+    let commit_oid = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        &message,
+        &tree,
+        &parent_refs,
+    )?;
+
+    repo.cleanup_state()?;
+
+    Ok(MergeCommitResult {
+        commit_oid: commit_oid.to_string(),
+        parent_oids: parents
+            .iter()
+            .map(|commit| commit.id().to_string())
+            .collect(),
+    })
+}
+
+/// Like `git merge --abort`: puts back the paths the merge changed in the
+/// index, conflicts included, and keeps local changes to any other file.
+#[command]
+pub fn abort_merge(app: AppHandle, repo_path: Option<String>) -> Result<()> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    ensure_merge_in_progress(&repo)?;
+
+    let head_commit = repo.head()?.peel_to_commit()?;
+    let head_tree = head_commit.tree()?;
+    let index = repo.index()?;
+
+    let mut paths: BTreeSet<String> = BTreeSet::new();
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        for entry in [conflict.ancestor, conflict.our, conflict.their]
+            .into_iter()
+            .flatten()
+        {
+            paths.insert(String::from_utf8_lossy(&entry.path).to_string());
+        }
+    }
+    let diff = repo.diff_tree_to_index(Some(&head_tree), Some(&index), None)?;
+    for delta in diff.deltas() {
+        for file in [delta.old_file(), delta.new_file()] {
+            if let Some(path) = file.path() {
+                paths.insert(path.to_string_lossy().to_string());
+            }
+        }
+    }
+
+    if !paths.is_empty() {
+        repo.reset_default(Some(head_commit.as_object()), paths.iter())?;
+
+        let mut checkout_builder = CheckoutBuilder::new();
+        checkout_builder
+            .force()
+            .recreate_missing(true)
+            .disable_pathspec_match(true);
+        for path in &paths {
+            remove_worktree_file(&repo, path)?;
+            checkout_builder.path(path.as_str());
+        }
+        repo.checkout_head(Some(&mut checkout_builder))?;
+    }
+
+    repo.cleanup_state()?;
+
+    Ok(())
+}
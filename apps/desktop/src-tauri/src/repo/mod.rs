@@ -1,10 +1,23 @@
+pub mod auto_fetch;
 pub mod branch;
+pub mod checkout;
 pub mod clone;
 pub mod commit;
+pub mod credentials;
+pub mod diff;
+pub mod discard;
 pub mod error;
 pub mod file;
+pub mod merge;
+pub mod operation;
+pub mod pull;
+pub mod rebase;
 pub mod remote;
+pub mod reset;
+pub mod sequencer;
+pub mod ssh;
 pub mod staging;
+pub mod stash;
 pub mod status;
 
 use crate::{
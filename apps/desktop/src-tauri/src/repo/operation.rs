@@ -0,0 +1,142 @@
+use std::{
+    collections::HashMap,
+    ops::Deref,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use parking_lot::{const_mutex, Mutex};
+use serde::Serialize;
+use tauri::command;
+
+use crate::repo::error::{Error, Result};
+
+#[derive(Clone, Serialize)]
+pub struct OperationInfo {
+    operation_id: String,
+    kind: String,
+}
+
+/// A running clone, fetch, push or pull. Transfer callbacks poll
+/// `is_cancelled` and return `false` to make libgit2 stop.
+#[derive(Clone)]
+pub(crate) struct Operation {
+    id: String,
+    kind: String,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Operation {
+    pub(crate) fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Turns a failure caused by cancelling into `Error::Cancelled`, so callers
+    /// can report it apart from real errors.
+    pub(crate) fn check<T>(&self, result: Result<T>) -> Result<T> {
+        match result {
+            Err(_) if self.is_cancelled() => Err(Error::Cancelled(self.id.clone())),
+            result => result,
+        }
+    }
+}
+
+/// Keeps an operation registered until it is dropped.
+pub(crate) struct OperationGuard(Operation);
+
+impl Deref for OperationGuard {
+    type Target = Operation;
+
+    fn deref(&self) -> &Operation {
+        &self.0
+    }
+}
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        if let Some(operations) = &mut *OPERATIONS.lock() {
+            operations.remove(&self.0.id);
+        }
+    }
+}
+
+static OPERATIONS: Mutex<Option<HashMap<String, Operation>>> = const_mutex(None);
+static NEXT_OPERATION: AtomicU64 = AtomicU64::new(1);
+
+/// Runs blocking libgit2 work on the blocking thread pool, so a long
+/// transfer doesn't hold up one of the async runtime's workers.
+pub(crate) async fn run_blocking<T, F>(work: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(work).await?
+}
+
+/// Registers an operation under the id the frontend picked, or a generated
+/// one. The frontend needs the id up front to cancel while the command is
+/// still awaited.
+pub(crate) fn start_operation(kind: &str, operation_id: Option<String>) -> Result<OperationGuard> {
+    let id = operation_id
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| format!("{}-{}", kind, NEXT_OPERATION.fetch_add(1, Ordering::SeqCst)));
+
+    let mut guard = OPERATIONS.lock();
+    let operations = guard.get_or_insert_with(HashMap::new);
+    if operations.contains_key(&id) {
+        return Err(Error::OperationError(format!(
+            "An operation with id {} is already running",
+            id
+        )));
+    }
+
+    let operation = Operation {
+        id: id.clone(),
+        kind: kind.to_string(),
+        cancelled: Arc::new(AtomicBool::new(false)),
+    };
+    operations.insert(id, operation.clone());
+
+    Ok(OperationGuard(operation))
+}
+
+/// Asks a running operation to stop. Returns `false` when no operation has
+/// that id, e.g. because it already finished.
+#[command]
+pub fn cancel_operation(operation_id: String) -> bool {
+    match OPERATIONS
+        .lock()
+        .as_ref()
+        .and_then(|operations| operations.get(&operation_id))
+    {
+        Some(operation) => {
+            operation.cancelled.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
+
+#[command]
+pub fn list_operations() -> Vec<OperationInfo> {
+    OPERATIONS
+        .lock()
+        .as_ref()
+        .map(|operations| {
+            operations
+                .values()
+                .map(|operation| OperationInfo {
+                    operation_id: operation.id.clone(),
+                    kind: operation.kind.clone(),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
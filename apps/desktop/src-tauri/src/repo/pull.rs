@@ -0,0 +1,301 @@
+use std::fs;
+
+use git2::{build::CheckoutBuilder, Repository, RepositoryState};
+use serde::Serialize;
+use tauri::{command, AppHandle};
+
+use crate::repo::{
+    branch::get_branch_oid,
+    checkout::{checkout_blockers, dirty_paths_touched_by, status_paths},
+    error::{Error, Result},
+    open_repo,
+    operation::{run_blocking, start_operation},
+    rebase::{start_rebase, RebaseProgress},
+    remote::{fetch_remote, FetchRequestOptions},
+    status::{conflict_entries, ConflictEntry},
+};
+
+#[derive(Clone, Copy, PartialEq)]
+enum PullStrategy {
+    FastForwardOnly,
+    Merge,
+    Rebase,
+}
+
+#[derive(Serialize)]
+pub struct PullResult {
+    strategy: String,
+    status: String,
+    branch_name: String,
+    upstream: String,
+    head_oid: String,
+    blocking_paths: Vec<String>,
+    conflicts: Vec<ConflictEntry>,
+    rebase: Option<RebaseProgress>,
+    credential_method: Option<String>,
+}
+
+fn strategy_label(strategy: PullStrategy) -> &'static str {
+    match strategy {
+        PullStrategy::FastForwardOnly => "ff_only",
+        PullStrategy::Merge => "merge",
+        PullStrategy::Rebase => "rebase",
+    }
+}
+
+/// An explicit `strategy` wins, then `branch.<name>.rebase` / `pull.rebase`,
+/// then `pull.ff`. The flag is set when `pull.ff=false` asks for a merge
+/// commit even when a fast-forward is possible.
+fn resolve_strategy(
+    repo: &Repository,
+    branch_name: &str,
+    strategy: Option<&str>,
+) -> Result<(PullStrategy, bool)> {
+    let config = repo.config()?.snapshot()?;
+    let pull_ff = config.get_string("pull.ff").ok();
+    let no_ff = pull_ff.as_deref() == Some("false");
+
+    if let Some(strategy) = strategy {
+        return match strategy {
+            "ff_only" => Ok((PullStrategy::FastForwardOnly, false)),
+            "merge" => Ok((PullStrategy::Merge, no_ff)),
+            "rebase" => Ok((PullStrategy::Rebase, false)),
+            other => Err(Error::RemoteError(format!(
+                "Unknown pull strategy: {}",
+                other
+            ))),
+        };
+    }
+
+    // `pull.rebase` also accepts `merges` and `interactive`, which still
+    // mean rebasing.
+    let rebase = config
+        .get_string(&format!("branch.{}.rebase", branch_name))
+        .or_else(|_| config.get_string("pull.rebase"))
+        .ok()
+        .map(|value| !matches!(value.as_str(), "false" | "no" | "off" | "0"))
+        .unwrap_or(false);
+
+    if rebase {
+        Ok((PullStrategy::Rebase, false))
+    } else if pull_ff.as_deref() == Some("only") {
+        Ok((PullStrategy::FastForwardOnly, false))
+    } else {
+        Ok((PullStrategy::Merge, no_ff))
+    }
+}
+
+/// Pulls the current branch from its upstream. `remote_name` pulls the same
+/// branch from another remote instead, e.g. `upstream` in a fork.
+#[command]
+pub async fn pull_repo(
+    app: AppHandle,
+    repo_path: Option<String>,
+    token: Option<String>,
+    strategy: Option<String>,
+    remote_name: Option<String>,
+    operation_id: Option<String>,
+) -> Result<PullResult> {
+    run_blocking(move || {
+        let repo = open_repo(app.clone(), repo_path)?;
+        let operation = start_operation("pull", operation_id)?;
+
+        if repo.state() != RepositoryState::Clean {
+            return Err(Error::RemoteError(
+                "Cannot pull while another operation is in progress".to_string(),
+            ));
+        }
+
+        let head = repo.head()?;
+        if !head.is_branch() {
+            return Err(Error::RemoteError(
+                "Cannot pull while HEAD is detached".to_string(),
+            ));
+        }
+        let branch_name = head
+            .shorthand()
+            .map(|value| value.to_string())
+            .ok_or_else(|| {
+                Error::RemoteError("Unable to resolve current branch name".to_string())
+            })?;
+        let ref_name = format!("refs/heads/{}", branch_name);
+
+        let upstream_remote = repo
+            .branch_upstream_remote(&ref_name)
+            .ok()
+            .and_then(|buf| buf.as_str().map(|value| value.to_string()));
+        let merge_branch = repo
+            .branch_upstream_merge(&ref_name)
+            .ok()
+            .and_then(|buf| buf.as_str().map(|value| value.to_string()))
+            .and_then(|merge| {
+                merge
+                    .strip_prefix("refs/heads/")
+                    .map(|value| value.to_string())
+            })
+            .unwrap_or_else(|| branch_name.clone());
+        let remote_name = remote_name.or(upstream_remote).ok_or_else(|| {
+            Error::RemoteError(format!(
+                "{} has no upstream branch to pull from",
+                branch_name
+            ))
+        })?;
+        let (pull_strategy, no_ff) = resolve_strategy(&repo, &branch_name, strategy.as_deref())?;
+
+        let upstream_name = format!("{}/{}", remote_name, merge_branch);
+        let upstream_ref_name = format!("refs/remotes/{}", upstream_name);
+        let local_oid = get_branch_oid(&repo, &branch_name)?;
+
+        let mut result = PullResult {
+            strategy: strategy_label(pull_strategy).to_string(),
+            status: "up_to_date".to_string(),
+            branch_name: branch_name.clone(),
+            upstream: upstream_name.clone(),
+            head_oid: local_oid.to_string(),
+            blocking_paths: Vec::new(),
+            conflicts: Vec::new(),
+            rebase: None,
+            credential_method: None,
+        };
+
+        // Only the fetch can be cancelled; once it's done the pull runs to the end.
+        let fetched = fetch_remote(
+            Some(&app),
+            &repo,
+            &remote_name,
+            token,
+            &FetchRequestOptions::default(),
+            Some(&operation),
+        );
+        if let Err(Error::Cancelled(_)) = fetched {
+            result.status = "cancelled".to_string();
+            return Ok(result);
+        }
+        result.credential_method = fetched?.credential_method;
+
+        let upstream_oid = repo
+            .find_reference(&upstream_ref_name)
+            .map_err(|_| Error::RemoteError(format!("{} does not exist", upstream_name)))?
+            .peel_to_commit()?
+            .id();
+
+        if local_oid == upstream_oid || repo.graph_descendant_of(local_oid, upstream_oid)? {
+            return Ok(result);
+        }
+
+        let can_fast_forward = repo.graph_descendant_of(upstream_oid, local_oid)?;
+
+        if can_fast_forward && !(pull_strategy == PullStrategy::Merge && no_ff) {
+            let blockers = checkout_blockers(&repo, upstream_oid)?;
+            if !blockers.is_empty() {
+                result.status = "blocked".to_string();
+                result.blocking_paths = blockers;
+                return Ok(result);
+            }
+
+            let upstream_commit = repo.find_commit(upstream_oid)?;
+            let mut checkout_builder = CheckoutBuilder::new();
+            checkout_builder.safe();
+            repo.checkout_tree(upstream_commit.as_object(), Some(&mut checkout_builder))?;
+            repo.find_reference(&ref_name)?.set_target(
+                upstream_oid,
+                &format!("pull: fast-forward to {}", upstream_name),
+            )?;
+
+            result.status = "fast_forwarded".to_string();
+            result.head_oid = upstream_oid.to_string();
+            return Ok(result);
+        }
+
+        match pull_strategy {
+            PullStrategy::FastForwardOnly => {
+                result.status = "diverged".to_string();
+            }
+            PullStrategy::Rebase => {
+                // Every step of the rebase rewrites the working tree, so like
+                // `git pull --rebase` any tracked change blocks it.
+                let blockers = status_paths(&repo, false)?;
+                if !blockers.is_empty() {
+                    result.status = "blocked".to_string();
+                    result.blocking_paths = blockers;
+                    return Ok(result);
+                }
+
+                let progress = start_rebase(
+                    &repo,
+                    Some(branch_name.clone()),
+                    &upstream_ref_name,
+                    None,
+                    None,
+                )?;
+                result.status = if progress.status == "completed" {
+                    "rebased".to_string()
+                } else {
+                    "conflicts".to_string()
+                };
+                result.head_oid = repo.head()?.peel_to_commit()?.id().to_string();
+                result.conflicts = conflict_entries(&repo.index()?)?;
+                result.rebase = Some(progress);
+            }
+            PullStrategy::Merge => {
+                let merge_base = repo.merge_base(local_oid, upstream_oid)?;
+                let blockers = dirty_paths_touched_by(
+                    &repo,
+                    Some(&repo.find_commit(merge_base)?.tree()?),
+                    &repo.find_commit(upstream_oid)?.tree()?,
+                )?;
+                if !blockers.is_empty() {
+                    result.status = "blocked".to_string();
+                    result.blocking_paths = blockers;
+                    return Ok(result);
+                }
+
+                let remote_url = repo
+                    .find_remote(&remote_name)?
+                    .url()
+                    .unwrap_or(&remote_name)
+                    .to_string();
+                let message = format!("Merge branch '{}' of {}", merge_branch, remote_url);
+
+                let upstream_reference = repo.find_reference(&upstream_ref_name)?;
+                let upstream_annotated = repo.reference_to_annotated_commit(&upstream_reference)?;
+                let mut checkout_builder = CheckoutBuilder::new();
+                checkout_builder
+                    .safe()
+                    .allow_conflicts(true)
+                    .conflict_style_merge(true);
+                repo.merge(&[&upstream_annotated], None, Some(&mut checkout_builder))?;
+
+                let mut index = repo.index()?;
+                if index.has_conflicts() {
+                    // Left in progress for the `repo::merge` commands to conclude.
+                    fs::write(repo.path().join("MERGE_MSG"), format!("{}\n", message))?;
+                    result.status = "conflicts".to_string();
+                    result.conflicts = conflict_entries(&index)?;
+                    return Ok(result);
+                }
+
+                let tree = repo.find_tree(index.write_tree()?)?;
+                let signature = repo.signature()?;
+                let commit_oid = repo.commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    &message,
+                    &tree,
+                    &[
+                        &repo.find_commit(local_oid)?,
+                        &repo.find_commit(upstream_oid)?,
+                    ],
+                )?;
+                repo.cleanup_state()?;
+
+                result.status = "merged".to_string();
+                result.head_oid = commit_oid.to_string();
+            }
+        }
+
+        Ok(result)
+    })
+    .await
+}
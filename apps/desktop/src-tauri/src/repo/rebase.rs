@@ -0,0 +1,503 @@
+use std::{fs, path::PathBuf};
+
+use git2::{
+    build::CheckoutBuilder, CherrypickOptions, Oid, Repository, RepositoryState, ResetType, Sort,
+};
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle};
+
+use crate::repo::{
+    branch::get_branch_oid,
+    checkout::status_paths,
+    error::{Error, Result},
+    open_repo,
+    status::{conflict_entries, ConflictEntry},
+};
+
+/// Rebases run on our own cherry-pick loop, since libgit2's `Rebase` only
+/// picks. Its state lives here, so git itself doesn't see the rebase; the
+/// app has to continue or abort it.
+const REBASE_STATE_FILE: &str = "better-github-rebase.json";
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RebaseAction {
+    Pick,
+    Reword,
+    Squash,
+    Fixup,
+    Drop,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RebaseTodoItem {
+    pub oid: String,
+    pub action: RebaseAction,
+    pub message: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct RebasePlanEntry {
+    oid: String,
+    short_oid: String,
+    summary: String,
+    action: RebaseAction,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RebaseState {
+    branch_name: Option<String>,
+    original_head: String,
+    todo: Vec<RebaseTodoItem>,
+    /// Index of the next step to apply.
+    next: usize,
+    stopped_at: Option<usize>,
+    /// HEAD the stopped step gets committed onto. Once HEAD moved on, the
+    /// step was committed already.
+    #[serde(default)]
+    stopped_onto: Option<String>,
+    last_rewritten: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct RebaseProgress {
+    pub(crate) status: String,
+    branch_name: Option<String>,
+    current_step: Option<usize>,
+    total_steps: usize,
+    current: Option<RebaseTodoItem>,
+    conflicts: Vec<ConflictEntry>,
+    head_oid: Option<String>,
+}
+
+fn state_path(repo: &Repository) -> PathBuf {
+    repo.path().join(REBASE_STATE_FILE)
+}
+
+fn load_state(repo: &Repository) -> Result<Option<RebaseState>> {
+    let path = state_path(repo);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(path)?;
+    Ok(Some(serde_json::from_str(&content)?))
+}
+
+fn save_state(repo: &Repository, state: &RebaseState) -> Result<()> {
+    fs::write(state_path(repo), serde_json::to_string(state)?)?;
+    Ok(())
+}
+
+fn clear_state(repo: &Repository) -> Result<()> {
+    let path = state_path(repo);
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+fn require_state(repo: &Repository) -> Result<RebaseState> {
+    load_state(repo)?.ok_or_else(|| Error::RebaseError("No rebase is in progress".to_string()))
+}
+
+fn hard_reset(repo: &Repository, oid: Oid) -> Result<()> {
+    let commit = repo.find_commit(oid)?;
+    let mut checkout_builder = CheckoutBuilder::new();
+    checkout_builder.force();
+    repo.reset(
+        commit.as_object(),
+        ResetType::Hard,
+        Some(&mut checkout_builder),
+    )?;
+    Ok(())
+}
+
+fn resolve_commit(repo: &Repository, spec: &str) -> Result<Oid> {
+    Ok(repo.revparse_single(spec)?.peel_to_commit()?.id())
+}
+
+/// Commits that `git rebase <upstream> <branch>` would replay, oldest first.
+/// Merge commits are left out the same way libgit2 leaves them out.
+fn plan_commits(repo: &Repository, tip: Oid, upstream: Oid) -> Result<Vec<Oid>> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(tip)?;
+    revwalk.hide(upstream)?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+
+    let mut commits: Vec<Oid> = Vec::new();
+    for oid in revwalk {
+        let oid = oid?;
+        if repo.find_commit(oid)?.parent_count() <= 1 {
+            commits.push(oid);
+        }
+    }
+
+    Ok(commits)
+}
+
+/// Applies a step's commit on top of HEAD, leaving the result in the index
+/// and working tree, conflicts included.
+fn apply_step(repo: &Repository, item: &RebaseTodoItem) -> Result<()> {
+    let commit = repo.find_commit(Oid::from_str(&item.oid)?)?;
+
+    let mut checkout_builder = CheckoutBuilder::new();
+    checkout_builder
+        .allow_conflicts(true)
+        .conflict_style_merge(true);
+    let mut options = CherrypickOptions::new();
+    options.checkout_builder(checkout_builder);
+
+    repo.cherrypick(&commit, Some(&mut options))?;
+    Ok(())
+}
+
+fn progress(
+    repo: &Repository,
+    state: &RebaseState,
+    status: &str,
+    conflicts: Vec<ConflictEntry>,
+) -> RebaseProgress {
+    let head_oid = repo
+        .head()
+        .ok()
+        .and_then(|head| head.target())
+        .map(|oid| oid.to_string());
+
+    RebaseProgress {
+        status: status.to_string(),
+        branch_name: state.branch_name.clone(),
+        current_step: state.stopped_at,
+        total_steps: state.todo.len(),
+        current: state
+            .stopped_at
+            .and_then(|step| state.todo.get(step).cloned()),
+        conflicts,
+        head_oid,
+    }
+}
+
+/// Commits what the step left in the index, according to the action the
+/// user picked for it. Squash and fixup fold it into the commit before.
+fn commit_step(repo: &Repository, state: &mut RebaseState, step: usize) -> Result<()> {
+    let item =
+        state.todo.get(step).cloned().ok_or_else(|| {
+            Error::RebaseError(format!("Rebase step {} is out of range", step + 1))
+        })?;
+    let source = repo.find_commit(Oid::from_str(&item.oid)?)?;
+    let committer = repo.signature()?;
+
+    let mut index = repo.index()?;
+    index.read(true)?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let head_commit = repo.head()?.peel_to_commit()?;
+
+    let squashing = matches!(item.action, RebaseAction::Squash | RebaseAction::Fixup)
+        && state.last_rewritten.is_some();
+
+    if squashing {
+        let message = match (item.action, item.message.as_deref()) {
+            (RebaseAction::Squash, Some(message)) => message.to_string(),
+            (RebaseAction::Squash, None) => format!(
+                "{}\n\n{}",
+                head_commit.message().unwrap_or("").trim_end(),
+                source.message().unwrap_or("")
+            ),
+            _ => head_commit.message().unwrap_or("").to_string(),
+        };
+
+        let amended = head_commit.amend(
+            Some("HEAD"),
+            None,
+            Some(&committer),
+            None,
+            Some(&message),
+            Some(&tree),
+        )?;
+        state.last_rewritten = Some(amended.to_string());
+    } else if tree.id() != head_commit.tree_id() {
+        let message = match (item.action, item.message.as_deref()) {
+            (RebaseAction::Reword, Some(message)) => message,
+            _ => source.message().unwrap_or(""),
+        };
+
+        let oid = repo.commit(
+            Some("HEAD"),
+            &source.author(),
+            &committer,
+            message,
+            &tree,
+            &[&head_commit],
+        )?;
+        state.last_rewritten = Some(oid.to_string());
+    }
+    // Otherwise the change is already part of the new base, nothing to record.
+
+    repo.cleanup_state()?;
+    Ok(())
+}
+
+/// Commits the stopped step unless that already happened before the app
+/// went away, then records it as done.
+fn finish_step(repo: &Repository, state: &mut RebaseState) -> Result<()> {
+    let Some(step) = state.stopped_at else {
+        return Ok(());
+    };
+
+    let head_oid = repo.head()?.peel_to_commit()?.id().to_string();
+    if state.stopped_onto.as_deref() == Some(head_oid.as_str()) {
+        commit_step(repo, state, step)?;
+    } else {
+        repo.cleanup_state()?;
+        state.last_rewritten = Some(head_oid);
+    }
+
+    state.stopped_at = None;
+    state.stopped_onto = None;
+    save_state(repo, state)
+}
+
+fn run_rebase(repo: &Repository, mut state: RebaseState) -> Result<RebaseProgress> {
+    while let Some(item) = state.todo.get(state.next).cloned() {
+        let step = state.next;
+        // A step that fails to apply stays next, to be retried or skipped.
+        apply_step(repo, &item)?;
+        state.next += 1;
+
+        // Saved before committing, so a crash in between can't replay it.
+        state.stopped_at = Some(step);
+        state.stopped_onto = Some(repo.head()?.peel_to_commit()?.id().to_string());
+        save_state(repo, &state)?;
+
+        let mut index = repo.index()?;
+        index.read(true)?;
+        if index.has_conflicts() {
+            return Ok(progress(
+                repo,
+                &state,
+                "conflicts",
+                conflict_entries(&index)?,
+            ));
+        }
+
+        finish_step(repo, &mut state)?;
+    }
+
+    // The branch only moves once every step went through, so an abort
+    // before this leaves it untouched.
+    if let Some(name) = &state.branch_name {
+        let head_oid = repo.head()?.peel_to_commit()?.id();
+        let ref_name = format!("refs/heads/{}", name);
+        repo.reference(&ref_name, head_oid, true, "rebase (finish)")?;
+        repo.set_head(&ref_name)?;
+    }
+
+    state.stopped_at = None;
+    clear_state(repo)?;
+
+    Ok(progress(repo, &state, "completed", Vec::new()))
+}
+
+/// Starts a rebase of `branch_name` (or the checked out branch) onto `onto`,
+/// defaulting to `upstream`. Shared with pull so both go through one engine.
+pub(crate) fn start_rebase(
+    repo: &Repository,
+    branch_name: Option<String>,
+    upstream: &str,
+    onto: Option<&str>,
+    todo: Option<Vec<RebaseTodoItem>>,
+) -> Result<RebaseProgress> {
+    if repo.state() != RepositoryState::Clean || load_state(repo)?.is_some() {
+        return Err(Error::RebaseError(
+            "Another operation is already in progress".to_string(),
+        ));
+    }
+    let dirty = status_paths(repo, false)?;
+    if !dirty.is_empty() {
+        return Err(Error::RebaseError(format!(
+            "Commit or stash your local changes first: {}",
+            dirty.join(", ")
+        )));
+    }
+
+    let branch_name = match branch_name {
+        Some(name) => Some(name),
+        None => repo
+            .head()
+            .ok()
+            .filter(|head| head.is_branch())
+            .and_then(|head| head.shorthand().map(|value| value.to_string())),
+    };
+
+    let tip = match branch_name.as_deref() {
+        Some(name) => get_branch_oid(repo, name)?,
+        None => repo.head()?.peel_to_commit()?.id(),
+    };
+    let upstream_oid = resolve_commit(repo, upstream)?;
+    let onto_oid = match onto {
+        Some(spec) => resolve_commit(repo, spec)?,
+        None => upstream_oid,
+    };
+
+    let todo: Vec<RebaseTodoItem> = match todo {
+        Some(items) => items,
+        None => plan_commits(repo, tip, upstream_oid)?
+            .into_iter()
+            .map(|oid| RebaseTodoItem {
+                oid: oid.to_string(),
+                action: RebaseAction::Pick,
+                message: None,
+            })
+            .collect(),
+    }
+    .into_iter()
+    .filter(|item| item.action != RebaseAction::Drop)
+    .collect();
+
+    for item in &todo {
+        repo.find_commit(Oid::from_str(&item.oid)?)?;
+    }
+
+    // The steps are replayed onto a detached HEAD, like git does.
+    let onto_commit = repo.find_commit(onto_oid)?;
+    let mut checkout_builder = CheckoutBuilder::new();
+    checkout_builder.safe();
+    repo.checkout_tree(onto_commit.as_object(), Some(&mut checkout_builder))?;
+    repo.set_head_detached(onto_oid)?;
+    repo.reference("ORIG_HEAD", tip, true, "rebase: updating ORIG_HEAD")?;
+
+    let state = RebaseState {
+        branch_name,
+        original_head: tip.to_string(),
+        todo,
+        next: 0,
+        stopped_at: None,
+        stopped_onto: None,
+        last_rewritten: None,
+    };
+    save_state(repo, &state)?;
+
+    run_rebase(repo, state)
+}
+
+#[command]
+pub fn get_rebase_plan(
+    app: AppHandle,
+    branch_name: Option<String>,
+    upstream: String,
+    repo_path: Option<String>,
+) -> Result<Vec<RebasePlanEntry>> {
+    let repo = open_repo(app.clone(), repo_path)?;
+
+    let tip = match branch_name.as_deref() {
+        Some(name) => get_branch_oid(&repo, name)?,
+        None => repo.head()?.peel_to_commit()?.id(),
+    };
+    let upstream_oid = resolve_commit(&repo, &upstream)?;
+
+    let mut plan: Vec<RebasePlanEntry> = Vec::new();
+    for oid in plan_commits(&repo, tip, upstream_oid)? {
+        let commit = repo.find_commit(oid)?;
+        plan.push(RebasePlanEntry {
+            oid: oid.to_string(),
+            short_oid: oid.to_string()[..7].to_string(),
+            summary: commit.summary().unwrap_or("").to_string(),
+            action: RebaseAction::Pick,
+        });
+    }
+
+    Ok(plan)
+}
+
+#[command]
+pub fn start_interactive_rebase(
+    app: AppHandle,
+    branch_name: Option<String>,
+    upstream: String,
+    onto: Option<String>,
+    todo: Vec<RebaseTodoItem>,
+    repo_path: Option<String>,
+) -> Result<RebaseProgress> {
+    let repo = open_repo(app.clone(), repo_path)?;
+
+    start_rebase(&repo, branch_name, &upstream, onto.as_deref(), Some(todo))
+}
+
+/// Reports an unfinished rebase, e.g. one left behind by an app restart.
+#[command]
+pub fn get_rebase_progress(
+    app: AppHandle,
+    repo_path: Option<String>,
+) -> Result<Option<RebaseProgress>> {
+    let repo = open_repo(app.clone(), repo_path)?;
+
+    let Some(state) = load_state(&repo)? else {
+        return Ok(None);
+    };
+
+    let index = repo.index()?;
+    let conflicts = conflict_entries(&index)?;
+    let status = if conflicts.is_empty() {
+        "stopped"
+    } else {
+        "conflicts"
+    };
+
+    Ok(Some(progress(&repo, &state, status, conflicts)))
+}
+
+#[command]
+pub fn continue_rebase(app: AppHandle, repo_path: Option<String>) -> Result<RebaseProgress> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    let mut state = require_state(&repo)?;
+
+    let index = repo.index()?;
+    if index.has_conflicts() {
+        return Ok(progress(
+            &repo,
+            &state,
+            "conflicts",
+            conflict_entries(&index)?,
+        ));
+    }
+
+    finish_step(&repo, &mut state)?;
+
+    run_rebase(&repo, state)
+}
+
+/// Drops the step that stopped, or the one that failed to apply.
+#[command]
+pub fn skip_rebase_step(app: AppHandle, repo_path: Option<String>) -> Result<RebaseProgress> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    let mut state = require_state(&repo)?;
+
+    hard_reset(&repo, repo.head()?.peel_to_commit()?.id())?;
+
+    if state.stopped_at.take().is_none() {
+        state.next += 1;
+    }
+    state.stopped_onto = None;
+    save_state(&repo, &state)?;
+
+    run_rebase(&repo, state)
+}
+
+/// Returns to the branch as it was before the rebase. The steps were only
+/// ever committed on a detached HEAD, so the branch itself never moved.
+#[command]
+pub fn abort_rebase(app: AppHandle, repo_path: Option<String>) -> Result<()> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    let state = require_state(&repo)?;
+
+    let original_head = Oid::from_str(&state.original_head)?;
+    hard_reset(&repo, original_head)?;
+    if let Some(name) = &state.branch_name {
+        repo.set_head(&format!("refs/heads/{}", name))?;
+    }
+    repo.cleanup_state()?;
+
+    clear_state(&repo)?;
+
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use std::{collections::HashSet, fs, path::PathBuf};
+
+use git2::{build::CheckoutBuilder, Oid, Repository, ResetType, StatusOptions};
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle};
+
+use crate::repo::{
+    discard::save_safety_stash,
+    error::{Error, Result},
+    open_repo,
+};
+
+const RESET_RECORD_FILE: &str = "better-github-reset.json";
+
+#[derive(Serialize, Deserialize)]
+struct ResetRecord {
+    previous_oid: String,
+    reset_oid: String,
+    mode: String,
+}
+
+#[derive(Serialize)]
+pub struct ResetResult {
+    status: String,
+    mode: String,
+    previous_oid: String,
+    new_oid: String,
+    dirty_paths: Vec<String>,
+    safety_stash_oid: Option<String>,
+}
+
+fn record_path(repo: &Repository) -> PathBuf {
+    repo.path().join(RESET_RECORD_FILE)
+}
+
+fn parse_mode(mode: &str) -> Result<ResetType> {
+    match mode {
+        "soft" => Ok(ResetType::Soft),
+        "mixed" => Ok(ResetType::Mixed),
+        "hard" => Ok(ResetType::Hard),
+        other => Err(Error::ResetError(format!("Unknown reset mode: {}", other))),
+    }
+}
+
+/// Tracked files with staged or unstaged edits, i.e. what a hard reset loses.
+fn dirty_paths(repo: &Repository) -> Result<Vec<String>> {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(false).include_ignored(false);
+
+    let statuses = repo.statuses(Some(&mut opts))?;
+    Ok(statuses
+        .iter()
+        .filter_map(|entry| entry.path().map(|path| path.to_string()))
+        .collect())
+}
+
+fn reset_head(
+    repo: &Repository,
+    target_oid: Oid,
+    mode: &str,
+    confirmed_paths: Option<Vec<String>>,
+) -> Result<ResetResult> {
+    let reset_type = parse_mode(mode)?;
+    let previous_oid = repo.head()?.peel_to_commit()?.id();
+    let target = repo.find_commit(target_oid)?;
+
+    let mut dirty = Vec::new();
+    let mut safety_stash = None;
+
+    if reset_type == ResetType::Hard {
+        dirty = dirty_paths(repo)?;
+        let confirmed: HashSet<String> = confirmed_paths.unwrap_or_default().into_iter().collect();
+
+        if dirty.iter().any(|path| !confirmed.contains(path)) {
+            return Ok(ResetResult {
+                status: "confirmation_required".to_string(),
+                mode: mode.to_string(),
+                previous_oid: previous_oid.to_string(),
+                new_oid: previous_oid.to_string(),
+                dirty_paths: dirty,
+                safety_stash_oid: None,
+            });
+        }
+
+        safety_stash = save_safety_stash(repo, &dirty, "changes before hard reset")?;
+    }
+
+    let mut checkout_builder = CheckoutBuilder::new();
+    checkout_builder.force();
+    repo.reset(target.as_object(), reset_type, Some(&mut checkout_builder))?;
+
+    repo.reference(
+        "ORIG_HEAD",
+        previous_oid,
+        true,
+        &format!("reset: moving to {}", target_oid),
+    )?;
+
+    let record = ResetRecord {
+        previous_oid: previous_oid.to_string(),
+        reset_oid: target_oid.to_string(),
+        mode: mode.to_string(),
+    };
+    fs::write(record_path(repo), serde_json::to_string(&record)?)?;
+
+    Ok(ResetResult {
+        status: "reset".to_string(),
+        mode: mode.to_string(),
+        previous_oid: previous_oid.to_string(),
+        new_oid: target_oid.to_string(),
+        dirty_paths: dirty,
+        safety_stash_oid: safety_stash.map(|oid| oid.to_string()),
+    })
+}
+
+/// Moves the current branch to `commit_oid`. A hard reset only goes ahead when
+/// every dirty path reported back by a previous call is passed in
+/// `confirmed_paths`.
+#[command]
+pub fn reset_to_commit(
+    app: AppHandle,
+    commit_oid: String,
+    mode: String,
+    confirmed_paths: Option<Vec<String>>,
+    repo_path: Option<String>,
+) -> Result<ResetResult> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    let target_oid = Oid::from_str(&commit_oid)?;
+
+    reset_head(&repo, target_oid, &mode, confirmed_paths)
+}
+
+#[command]
+pub fn undo_last_reset(
+    app: AppHandle,
+    confirmed_paths: Option<Vec<String>>,
+    repo_path: Option<String>,
+) -> Result<ResetResult> {
+    let repo = open_repo(app.clone(), repo_path)?;
+
+    let path = record_path(&repo);
+    if !path.exists() {
+        return Err(Error::ResetError("There is no reset to undo".to_string()));
+    }
+    let record: ResetRecord = serde_json::from_str(&fs::read_to_string(&path)?)?;
+
+    let head_oid = repo.head()?.peel_to_commit()?.id();
+    if head_oid.to_string() != record.reset_oid {
+        return Err(Error::ResetError(
+            "HEAD has moved since the last reset and it can no longer be undone".to_string(),
+        ));
+    }
+
+    let previous_oid = Oid::from_str(&record.previous_oid)?;
+    let result = reset_head(&repo, previous_oid, &record.mode, confirmed_paths)?;
+
+    if result.status == "reset" {
+        fs::remove_file(path)?;
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,349 @@
+use std::{fs, path::PathBuf};
+
+use git2::{
+    build::CheckoutBuilder, CherrypickOptions, Commit, Oid, Repository, RepositoryState, ResetType,
+    RevertOptions, Sort,
+};
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle};
+
+use crate::repo::{
+    checkout::status_paths,
+    error::{Error, Result},
+    open_repo,
+    status::{conflict_entries, ConflictEntry},
+};
+
+const SEQUENCER_STATE_FILE: &str = "better-github-sequencer.json";
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SequencerOperation {
+    CherryPick,
+    Revert,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SequencerState {
+    operation: SequencerOperation,
+    original_head: String,
+    mainline: Option<u32>,
+    current: Option<String>,
+    remaining: Vec<String>,
+    created: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct SequencerResult {
+    operation: String,
+    status: String,
+    created_commits: Vec<String>,
+    current_commit: Option<String>,
+    remaining: Vec<String>,
+    conflicts: Vec<ConflictEntry>,
+}
+
+fn state_path(repo: &Repository) -> PathBuf {
+    repo.path().join(SEQUENCER_STATE_FILE)
+}
+
+fn load_state(repo: &Repository) -> Result<Option<SequencerState>> {
+    let path = state_path(repo);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(path)?;
+    Ok(Some(serde_json::from_str(&content)?))
+}
+
+fn save_state(repo: &Repository, state: &SequencerState) -> Result<()> {
+    fs::write(state_path(repo), serde_json::to_string(state)?)?;
+    Ok(())
+}
+
+fn clear_state(repo: &Repository) -> Result<()> {
+    let path = state_path(repo);
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+fn operation_label(operation: SequencerOperation) -> &'static str {
+    match operation {
+        SequencerOperation::CherryPick => "cherry_pick",
+        SequencerOperation::Revert => "revert",
+    }
+}
+
+/// Expands `from..to` specs into their commits and keeps single oids as-is.
+/// Ranges come out oldest first, or newest first when `newest_first` is set.
+fn expand_commits(repo: &Repository, specs: &[String], newest_first: bool) -> Result<Vec<String>> {
+    let mut commits: Vec<String> = Vec::new();
+
+    for spec in specs {
+        if spec.contains("..") {
+            let mut revwalk = repo.revwalk()?;
+            revwalk.push_range(spec)?;
+            if newest_first {
+                revwalk.set_sorting(Sort::TOPOLOGICAL)?;
+            } else {
+                revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+            }
+
+            for oid in revwalk {
+                commits.push(oid?.to_string());
+            }
+        } else {
+            let commit = repo.revparse_single(spec)?.peel_to_commit()?;
+            commits.push(commit.id().to_string());
+        }
+    }
+
+    if commits.is_empty() {
+        return Err(Error::SequencerError(
+            "No commits were selected".to_string(),
+        ));
+    }
+
+    Ok(commits)
+}
+
+fn revert_message(commit: &Commit) -> String {
+    format!(
+        "Revert \"{}\"\n\nThis reverts commit {}.\n",
+        commit.summary().unwrap_or(""),
+        commit.id()
+    )
+}
+
+/// Commits whatever the cherry-pick or revert left in the index. Returns
+/// `None` when the change turned out to be empty.
+fn commit_current(
+    repo: &Repository,
+    operation: SequencerOperation,
+    source: &Commit,
+) -> Result<Option<Oid>> {
+    let mut index = repo.index()?;
+    let tree_oid = index.write_tree()?;
+    let head_commit = repo.head()?.peel_to_commit()?;
+
+    if head_commit.tree_id() == tree_oid {
+        return Ok(None);
+    }
+
+    let tree = repo.find_tree(tree_oid)?;
+    let committer = repo.signature()?;
+    let (author, message) = match operation {
+        SequencerOperation::CherryPick => (
+            source.author().to_owned(),
+            source.message().unwrap_or("").to_string(),
+        ),
+        SequencerOperation::Revert => (committer.clone(), revert_message(source)),
+    };
+
+    let commit_oid = repo.commit(
+        Some("HEAD"),
+        &author,
+        &committer,
+        &message,
+        &tree,
+        &[&head_commit],
+    )?;
+
+    Ok(Some(commit_oid))
+}
+
+fn sequencer_result(
+    state: &SequencerState,
+    status: &str,
+    conflicts: Vec<ConflictEntry>,
+) -> SequencerResult {
+    SequencerResult {
+        operation: operation_label(state.operation).to_string(),
+        status: status.to_string(),
+        created_commits: state.created.clone(),
+        current_commit: state.current.clone(),
+        remaining: state.remaining.clone(),
+        conflicts,
+    }
+}
+
+fn run_sequence(repo: &Repository, mut state: SequencerState) -> Result<SequencerResult> {
+    while !state.remaining.is_empty() {
+        let oid = Oid::from_str(&state.remaining[0])?;
+        let commit = repo.find_commit(oid)?;
+
+        let mut checkout_builder = CheckoutBuilder::new();
+        checkout_builder
+            .allow_conflicts(true)
+            .conflict_style_merge(true);
+
+        let applied = match state.operation {
+            SequencerOperation::CherryPick => {
+                let mut options = CherrypickOptions::new();
+                options.checkout_builder(checkout_builder);
+                if let Some(mainline) = state.mainline {
+                    options.mainline(mainline);
+                }
+                repo.cherrypick(&commit, Some(&mut options))
+            }
+            SequencerOperation::Revert => {
+                let mut options = RevertOptions::new();
+                options.checkout_builder(checkout_builder);
+                if let Some(mainline) = state.mainline {
+                    options.mainline(mainline);
+                }
+                repo.revert(&commit, Some(&mut options))
+            }
+        };
+
+        // Keep the state around once commits were created so they can still
+        // be aborted, otherwise there is nothing to resume.
+        if let Err(err) = applied {
+            if state.created.is_empty() {
+                clear_state(repo)?;
+            } else {
+                save_state(repo, &state)?;
+            }
+            return Err(err.into());
+        }
+
+        state.remaining.remove(0);
+
+        let mut index = repo.index()?;
+        index.read(true)?;
+        if index.has_conflicts() {
+            state.current = Some(oid.to_string());
+            save_state(repo, &state)?;
+            return Ok(sequencer_result(
+                &state,
+                "conflicts",
+                conflict_entries(&index)?,
+            ));
+        }
+
+        if let Some(created) = commit_current(repo, state.operation, &commit)? {
+            state.created.push(created.to_string());
+        }
+        repo.cleanup_state()?;
+    }
+
+    state.current = None;
+    clear_state(repo)?;
+    Ok(sequencer_result(&state, "completed", Vec::new()))
+}
+
+fn start_sequence(
+    repo: &Repository,
+    operation: SequencerOperation,
+    commit_oids: Vec<String>,
+    mainline: Option<u32>,
+) -> Result<SequencerResult> {
+    if repo.state() != RepositoryState::Clean || load_state(repo)?.is_some() {
+        return Err(Error::SequencerError(
+            "Another operation is already in progress".to_string(),
+        ));
+    }
+    // Abort resets hard to the original HEAD, which is only safe when there
+    // was nothing uncommitted to lose.
+    let dirty = status_paths(repo, false)?;
+    if !dirty.is_empty() {
+        return Err(Error::SequencerError(format!(
+            "Commit or stash your local changes first: {}",
+            dirty.join(", ")
+        )));
+    }
+
+    let head_commit = repo.head()?.peel_to_commit()?;
+    let remaining = expand_commits(repo, &commit_oids, operation == SequencerOperation::Revert)?;
+
+    let state = SequencerState {
+        operation,
+        original_head: head_commit.id().to_string(),
+        mainline,
+        current: None,
+        remaining,
+        created: Vec::new(),
+    };
+
+    run_sequence(repo, state)
+}
+
+#[command]
+pub fn cherry_pick_commits(
+    app: AppHandle,
+    commit_oids: Vec<String>,
+    mainline: Option<u32>,
+    repo_path: Option<String>,
+) -> Result<SequencerResult> {
+    let repo = open_repo(app.clone(), repo_path)?;
+
+    start_sequence(&repo, SequencerOperation::CherryPick, commit_oids, mainline)
+}
+
+#[command]
+pub fn revert_commits(
+    app: AppHandle,
+    commit_oids: Vec<String>,
+    mainline: Option<u32>,
+    repo_path: Option<String>,
+) -> Result<SequencerResult> {
+    let repo = open_repo(app.clone(), repo_path)?;
+
+    start_sequence(&repo, SequencerOperation::Revert, commit_oids, mainline)
+}
+
+#[command]
+pub fn continue_sequence(app: AppHandle, repo_path: Option<String>) -> Result<SequencerResult> {
+    let repo = open_repo(app.clone(), repo_path)?;
+
+    let mut state = load_state(&repo)?.ok_or_else(|| {
+        Error::SequencerError("No cherry-pick or revert is in progress".to_string())
+    })?;
+
+    let mut index = repo.index()?;
+    if index.has_conflicts() {
+        return Ok(sequencer_result(
+            &state,
+            "conflicts",
+            conflict_entries(&index)?,
+        ));
+    }
+
+    if let Some(current) = state.current.take() {
+        let commit = repo.find_commit(Oid::from_str(&current)?)?;
+        index.write()?;
+        if let Some(created) = commit_current(&repo, state.operation, &commit)? {
+            state.created.push(created.to_string());
+        }
+        repo.cleanup_state()?;
+        save_state(&repo, &state)?;
+    }
+
+    run_sequence(&repo, state)
+}
+
+#[command]
+pub fn abort_sequence(app: AppHandle, repo_path: Option<String>) -> Result<()> {
+    let repo = open_repo(app.clone(), repo_path)?;
+
+    let state = load_state(&repo)?.ok_or_else(|| {
+        Error::SequencerError("No cherry-pick or revert is in progress".to_string())
+    })?;
+
+    let original_head = repo.find_commit(Oid::from_str(&state.original_head)?)?;
+    let mut checkout_builder = CheckoutBuilder::new();
+    checkout_builder.force();
+    repo.reset(
+        original_head.as_object(),
+        ResetType::Hard,
+        Some(&mut checkout_builder),
+    )?;
+
+    repo.cleanup_state()?;
+    clear_state(&repo)?;
+
+    Ok(())
+}
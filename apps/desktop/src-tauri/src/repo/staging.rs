@@ -1,11 +1,368 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
 use git2::Commit;
+use git2::Delta;
+use git2::DiffOptions;
 use git2::ErrorCode;
+use git2::FileMode;
+use git2::Index;
 use git2::IndexAddOption;
+use git2::IndexEntry;
+use git2::IndexTime;
+use git2::Oid;
+use git2::Patch;
 use git2::Repository;
+use serde::{Deserialize, Serialize};
 use tauri::{command, AppHandle};
 
+use crate::repo::error::Error;
 use crate::repo::{open_repo, Result};
 
+#[derive(Serialize)]
+pub struct HunkLineInfo {
+    index: usize,
+    origin: String,
+    old_lineno: Option<u32>,
+    new_lineno: Option<u32>,
+    content: String,
+}
+
+#[derive(Serialize)]
+pub struct HunkInfo {
+    index: usize,
+    header: String,
+    old_start: u32,
+    old_lines: u32,
+    new_start: u32,
+    new_lines: u32,
+    lines: Vec<HunkLineInfo>,
+}
+
+#[derive(Serialize)]
+pub struct FileHunks {
+    path: String,
+    status: String,
+    is_binary: bool,
+    hunks: Vec<HunkInfo>,
+}
+
+/// A hunk picked by the user. When `line_indices` is `None` the whole hunk
+/// is selected, otherwise only the listed `+`/`-` lines are.
+#[derive(Clone, Deserialize)]
+pub struct HunkSelection {
+    pub hunk_index: usize,
+    pub line_indices: Option<Vec<usize>>,
+}
+
+pub(crate) struct PatchLine {
+    origin: char,
+    content: Vec<u8>,
+}
+
+pub(crate) struct PatchHunk {
+    header: String,
+    old_start: u32,
+    old_lines: u32,
+    new_start: u32,
+    new_lines: u32,
+    lines: Vec<PatchLine>,
+}
+
+pub(crate) struct FilePatch {
+    pub(crate) status: Delta,
+    pub(crate) old_id: Oid,
+    pub(crate) new_id: Oid,
+    pub(crate) old_mode: FileMode,
+    pub(crate) new_mode: FileMode,
+    pub(crate) is_binary: bool,
+    pub(crate) hunks: Vec<PatchHunk>,
+}
+
+type Selection = HashMap<usize, Option<HashSet<usize>>>;
+
+fn get_head_commit(repo: &Repository) -> Result<Option<Commit<'_>>> {
+    match repo.head() {
+        Ok(head) => Ok(Some(head.peel_to_commit()?)),
+        Err(err) if matches!(err.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Builds the patch of a single file, either HEAD -> index (`staged`) or
+/// index -> working tree. Listing and applying a selection both go through
+/// here so hunk and line indices stay stable between the two calls.
+pub(crate) fn load_file_patch(
+    repo: &Repository,
+    index: &Index,
+    file_path: &str,
+    staged: bool,
+) -> Result<Option<FilePatch>> {
+    let mut opts = DiffOptions::new();
+    opts.pathspec(file_path)
+        .disable_pathspec_match(true)
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+
+    let diff = if staged {
+        let head_tree = match get_head_commit(repo)? {
+            Some(commit) => Some(commit.tree()?),
+            None => None,
+        };
+        repo.diff_tree_to_index(head_tree.as_ref(), Some(index), Some(&mut opts))?
+    } else {
+        repo.diff_index_to_workdir(Some(index), Some(&mut opts))?
+    };
+
+    if diff.deltas().len() == 0 {
+        return Ok(None);
+    }
+
+    let patch = match Patch::from_diff(&diff, 0)? {
+        Some(patch) => patch,
+        None => return Ok(None),
+    };
+
+    let delta = patch.delta();
+    let is_binary =
+        delta.flags().is_binary() || delta.old_file().is_binary() || delta.new_file().is_binary();
+
+    let mut hunks: Vec<PatchHunk> = Vec::new();
+    for hunk_idx in 0..patch.num_hunks() {
+        let (hunk, line_count) = patch.hunk(hunk_idx)?;
+        let mut lines: Vec<PatchLine> = Vec::new();
+
+        for line_idx in 0..line_count {
+            let line = patch.line_in_hunk(hunk_idx, line_idx)?;
+            lines.push(PatchLine {
+                origin: line.origin(),
+                content: line.content().to_vec(),
+            });
+        }
+
+        hunks.push(PatchHunk {
+            header: String::from_utf8_lossy(hunk.header())
+                .trim_end()
+                .to_string(),
+            old_start: hunk.old_start(),
+            old_lines: hunk.old_lines(),
+            new_start: hunk.new_start(),
+            new_lines: hunk.new_lines(),
+            lines,
+        });
+    }
+
+    Ok(Some(FilePatch {
+        status: delta.status(),
+        old_id: delta.old_file().id(),
+        new_id: delta.new_file().id(),
+        old_mode: delta.old_file().mode(),
+        new_mode: delta.new_file().mode(),
+        is_binary,
+        hunks,
+    }))
+}
+
+pub(crate) fn to_selection(selection: &[HunkSelection]) -> Selection {
+    let mut selected: Selection = HashMap::new();
+    for item in selection {
+        let lines = item
+            .line_indices
+            .as_ref()
+            .map(|indices| indices.iter().copied().collect::<HashSet<usize>>());
+        selected.insert(item.hunk_index, lines);
+    }
+    selected
+}
+
+fn is_line_selected(selection: &Selection, hunk_idx: usize, line_idx: usize) -> bool {
+    match selection.get(&hunk_idx) {
+        Some(None) => true,
+        Some(Some(lines)) => lines.contains(&line_idx),
+        None => false,
+    }
+}
+
+/// Returns true when every `origin` line of the patch is part of the selection.
+pub(crate) fn selects_all(patch: &FilePatch, selection: &Selection, origin: char) -> bool {
+    patch.hunks.iter().enumerate().all(|(hunk_idx, hunk)| {
+        hunk.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.origin == origin)
+            .all(|(line_idx, _)| is_line_selected(selection, hunk_idx, line_idx))
+    })
+}
+
+/// Returns true when at least one `+`/`-` line of the patch is selected.
+pub(crate) fn selects_any(patch: &FilePatch, selection: &Selection) -> bool {
+    patch.hunks.iter().enumerate().any(|(hunk_idx, hunk)| {
+        hunk.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| matches!(line.origin, '+' | '-'))
+            .any(|(line_idx, _)| is_line_selected(selection, hunk_idx, line_idx))
+    })
+}
+
+fn split_lines(content: &[u8]) -> Vec<&[u8]> {
+    let mut lines: Vec<&[u8]> = Vec::new();
+    let mut start = 0;
+    for (pos, byte) in content.iter().enumerate() {
+        if *byte == b'\n' {
+            lines.push(&content[start..=pos]);
+            start = pos + 1;
+        }
+    }
+    if start < content.len() {
+        lines.push(&content[start..]);
+    }
+    lines
+}
+
+fn uses_crlf(lines: &[&[u8]]) -> bool {
+    let mut terminated = lines.iter().filter(|line| line.ends_with(b"\n")).peekable();
+    terminated.peek().is_some() && terminated.all(|line| line.ends_with(b"\r\n"))
+}
+
+fn push_line(out: &mut Vec<u8>, line: &[u8], crlf: bool) {
+    if !out.is_empty() && !out.ends_with(b"\n") {
+        out.extend_from_slice(if crlf { b"\r\n" } else { b"\n" });
+    }
+
+    if crlf && line.ends_with(b"\n") && !line.ends_with(b"\r\n") {
+        out.extend_from_slice(&line[..line.len() - 1]);
+        out.extend_from_slice(b"\r\n");
+    } else {
+        out.extend_from_slice(line);
+    }
+}
+
+/// Rebuilds `base` with the selected lines of `patch` applied.
+///
+/// Going forward, `base` is the old side of the patch: selected `-` lines are
+/// dropped and selected `+` lines are inserted. In `reverse`, `base` is the new
+/// side and the selected lines are undone instead. Unchanged lines are always
+/// copied from `base` so its line endings survive untouched.
+pub(crate) fn apply_selection(
+    base: &[u8],
+    patch: &FilePatch,
+    selection: &Selection,
+    reverse: bool,
+) -> Vec<u8> {
+    let base_lines = split_lines(base);
+    let crlf = uses_crlf(&base_lines);
+    let (base_origin, incoming_origin) = if reverse { ('+', '-') } else { ('-', '+') };
+
+    let mut out: Vec<u8> = Vec::with_capacity(base.len());
+    let mut cursor = 0usize;
+
+    for (hunk_idx, hunk) in patch.hunks.iter().enumerate() {
+        let (start, count) = if reverse {
+            (hunk.new_start, hunk.new_lines)
+        } else {
+            (hunk.old_start, hunk.old_lines)
+        };
+        let hunk_begin = if count == 0 {
+            start as usize
+        } else {
+            (start as usize).saturating_sub(1)
+        };
+
+        while cursor < hunk_begin && cursor < base_lines.len() {
+            push_line(&mut out, base_lines[cursor], false);
+            cursor += 1;
+        }
+
+        for (line_idx, line) in hunk.lines.iter().enumerate() {
+            let selected = is_line_selected(selection, hunk_idx, line_idx);
+
+            if line.origin == ' ' {
+                let content = base_lines.get(cursor).copied().unwrap_or(&line.content);
+                push_line(&mut out, content, false);
+                cursor += 1;
+            } else if line.origin == base_origin {
+                if !selected {
+                    let content = base_lines.get(cursor).copied().unwrap_or(&line.content);
+                    push_line(&mut out, content, false);
+                }
+                cursor += 1;
+            } else if line.origin == incoming_origin && selected {
+                push_line(&mut out, &line.content, crlf);
+            }
+        }
+    }
+
+    while cursor < base_lines.len() {
+        push_line(&mut out, base_lines[cursor], false);
+        cursor += 1;
+    }
+
+    out
+}
+
+pub(crate) fn read_blob_or_empty(repo: &Repository, id: Oid) -> Result<Vec<u8>> {
+    if id.is_zero() {
+        return Ok(Vec::new());
+    }
+
+    Ok(repo.find_blob(id)?.content().to_vec())
+}
+
+pub(crate) fn pick_mode(preferred: FileMode, fallback: FileMode) -> u32 {
+    match preferred {
+        FileMode::Unreadable => u32::from(fallback),
+        mode => u32::from(mode),
+    }
+}
+
+fn write_index_blob(index: &mut Index, file_path: &str, mode: u32, content: &[u8]) -> Result<()> {
+    // Stat data is left zeroed so git re-hashes the file instead of trusting
+    // a size/mtime match against the working tree copy.
+    let entry = IndexEntry {
+        ctime: IndexTime::new(0, 0),
+        mtime: IndexTime::new(0, 0),
+        dev: 0,
+        ino: 0,
+        mode,
+        uid: 0,
+        gid: 0,
+        file_size: content.len() as u32,
+        id: Oid::zero(),
+        flags: 0,
+        flags_extended: 0,
+        path: file_path.as_bytes().to_vec(),
+    };
+
+    index.add_frombuffer(&entry, content)?;
+    Ok(())
+}
+
+fn require_patch(patch: Option<FilePatch>, file_path: &str) -> Result<FilePatch> {
+    let patch =
+        patch.ok_or_else(|| Error::StagingError(format!("No changes found for {}", file_path)))?;
+
+    if patch.is_binary {
+        return Err(Error::StagingError(format!(
+            "Cannot select hunks in binary file {}",
+            file_path
+        )));
+    }
+
+    Ok(patch)
+}
+
+fn delta_label(status: Delta) -> &'static str {
+    match status {
+        Delta::Added | Delta::Untracked => "New",
+        Delta::Deleted => "Deleted",
+        Delta::Renamed => "Renamed",
+        Delta::Typechange => "Typechange",
+        _ => "Modified",
+    }
+}
+
 #[command]
 pub fn stage_file(app: AppHandle, file_path: String, repo_path: Option<String>) -> Result<()> {
     let repo = open_repo(app.clone(), repo_path)?;
@@ -29,14 +386,6 @@ pub fn stage_all_files(app: AppHandle, repo_path: Option<String>) -> Result<()>
     Ok(())
 }
 
-fn get_head_commit(repo: &Repository) -> Result<Option<Commit>> {
-    match repo.head() {
-        Ok(head) => Ok(Some(head.peel_to_commit()?)),
-        Err(err) if matches!(err.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => Ok(None),
-        Err(err) => Err(err.into()),
-    }
-}
-
 #[command]
 pub fn unstage_file(app: AppHandle, file_path: String, repo_path: Option<String>) -> Result<()> {
     let repo = open_repo(app.clone(), repo_path)?;
@@ -60,3 +409,281 @@ pub fn unstage_all_files(app: AppHandle, repo_path: Option<String>) -> Result<()
 
     Ok(())
 }
+
+#[command]
+pub fn get_file_hunks(
+    app: AppHandle,
+    file_path: String,
+    staged: bool,
+    repo_path: Option<String>,
+) -> Result<FileHunks> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    let index = repo.index()?;
+
+    let patch = match load_file_patch(&repo, &index, &file_path, staged)? {
+        Some(patch) => patch,
+        None => {
+            return Ok(FileHunks {
+                path: file_path,
+                status: "Unmodified".to_string(),
+                is_binary: false,
+                hunks: Vec::new(),
+            })
+        }
+    };
+
+    let hunks = patch
+        .hunks
+        .iter()
+        .enumerate()
+        .map(|(hunk_idx, hunk)| {
+            let mut old_lineno = hunk.old_start;
+            let mut new_lineno = hunk.new_start;
+
+            let lines = hunk
+                .lines
+                .iter()
+                .enumerate()
+                .map(|(line_idx, line)| {
+                    let (old, new) = match line.origin {
+                        ' ' => {
+                            old_lineno += 1;
+                            new_lineno += 1;
+                            (Some(old_lineno - 1), Some(new_lineno - 1))
+                        }
+                        '-' => {
+                            old_lineno += 1;
+                            (Some(old_lineno - 1), None)
+                        }
+                        '+' => {
+                            new_lineno += 1;
+                            (None, Some(new_lineno - 1))
+                        }
+                        _ => (None, None),
+                    };
+
+                    HunkLineInfo {
+                        index: line_idx,
+                        origin: line.origin.to_string(),
+                        old_lineno: old,
+                        new_lineno: new,
+                        content: String::from_utf8_lossy(&line.content)
+                            .trim_end_matches(['\r', '\n'])
+                            .to_string(),
+                    }
+                })
+                .collect();
+
+            HunkInfo {
+                index: hunk_idx,
+                header: hunk.header.clone(),
+                old_start: hunk.old_start,
+                old_lines: hunk.old_lines,
+                new_start: hunk.new_start,
+                new_lines: hunk.new_lines,
+                lines,
+            }
+        })
+        .collect();
+
+    Ok(FileHunks {
+        path: file_path,
+        status: delta_label(patch.status).to_string(),
+        is_binary: patch.is_binary,
+        hunks,
+    })
+}
+
+#[command]
+pub fn stage_hunks(
+    app: AppHandle,
+    file_path: String,
+    selection: Vec<HunkSelection>,
+    repo_path: Option<String>,
+) -> Result<()> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    let mut index = repo.index()?;
+
+    let patch = require_patch(
+        load_file_patch(&repo, &index, &file_path, false)?,
+        &file_path,
+    )?;
+    let selection = to_selection(&selection);
+    if !selects_any(&patch, &selection) {
+        return Ok(());
+    }
+
+    if patch.status == Delta::Deleted && selects_all(&patch, &selection, '-') {
+        index.remove_path(Path::new(&file_path))?;
+    } else {
+        let base = read_blob_or_empty(&repo, patch.old_id)?;
+        let content = apply_selection(&base, &patch, &selection, false);
+        let mode = pick_mode(patch.old_mode, patch.new_mode);
+        write_index_blob(&mut index, &file_path, mode, &content)?;
+    }
+
+    index.write()?;
+
+    Ok(())
+}
+
+#[command]
+pub fn unstage_hunks(
+    app: AppHandle,
+    file_path: String,
+    selection: Vec<HunkSelection>,
+    repo_path: Option<String>,
+) -> Result<()> {
+    let repo = open_repo(app.clone(), repo_path)?;
+    let mut index = repo.index()?;
+
+    let patch = require_patch(
+        load_file_patch(&repo, &index, &file_path, true)?,
+        &file_path,
+    )?;
+    let selection = to_selection(&selection);
+    if !selects_any(&patch, &selection) {
+        return Ok(());
+    }
+
+    if patch.status == Delta::Added && selects_all(&patch, &selection, '+') {
+        index.remove_path(Path::new(&file_path))?;
+    } else {
+        let base = read_blob_or_empty(&repo, patch.new_id)?;
+        let content = apply_selection(&base, &patch, &selection, true);
+        let mode = pick_mode(patch.new_mode, patch.old_mode);
+        write_index_blob(&mut index, &file_path, mode, &content)?;
+    }
+
+    index.write()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(origin: char, content: &str) -> PatchLine {
+        PatchLine {
+            origin,
+            content: content.as_bytes().to_vec(),
+        }
+    }
+
+    fn patch(old: (u32, u32), new: (u32, u32), lines: Vec<PatchLine>) -> FilePatch {
+        FilePatch {
+            status: Delta::Modified,
+            old_id: Oid::zero(),
+            new_id: Oid::zero(),
+            old_mode: FileMode::Blob,
+            new_mode: FileMode::Blob,
+            is_binary: false,
+            hunks: vec![PatchHunk {
+                header: String::new(),
+                old_start: old.0,
+                old_lines: old.1,
+                new_start: new.0,
+                new_lines: new.1,
+                lines,
+            }],
+        }
+    }
+
+    fn select(line_indices: Option<Vec<usize>>) -> Selection {
+        to_selection(&[HunkSelection {
+            hunk_index: 0,
+            line_indices,
+        }])
+    }
+
+    fn apply(base: &str, patch: &FilePatch, selection: &Selection, reverse: bool) -> String {
+        String::from_utf8(apply_selection(base.as_bytes(), patch, selection, reverse)).unwrap()
+    }
+
+    #[test]
+    fn applies_selected_lines_only() {
+        let patch = patch(
+            (1, 3),
+            (1, 4),
+            vec![
+                line(' ', "1\n"),
+                line('-', "2\n"),
+                line('+', "X\n"),
+                line(' ', "3\n"),
+                line('+', "4\n"),
+            ],
+        );
+
+        assert_eq!(
+            apply("1\n2\n3\n", &patch, &select(None), false),
+            "1\nX\n3\n4\n"
+        );
+        assert_eq!(
+            apply("1\n2\n3\n", &patch, &select(Some(vec![1, 2])), false),
+            "1\nX\n3\n"
+        );
+        assert_eq!(
+            apply("1\n2\n3\n", &patch, &select(Some(vec![4])), false),
+            "1\n2\n3\n4\n"
+        );
+        assert_eq!(
+            apply("1\nX\n3\n4\n", &patch, &select(Some(vec![1, 2])), true),
+            "1\n2\n3\n4\n"
+        );
+        assert!(!selects_any(&patch, &select(Some(vec![0, 3]))));
+        assert!(!selects_any(&patch, &Selection::new()));
+    }
+
+    #[test]
+    fn keeps_crlf_line_endings() {
+        // Patches are made from the filtered content, so their lines end in
+        // LF even when the file on disk uses CRLF.
+        let patch = patch(
+            (1, 3),
+            (1, 3),
+            vec![
+                line(' ', "1\n"),
+                line('-', "2\n"),
+                line('+', "X\n"),
+                line(' ', "3\n"),
+            ],
+        );
+
+        assert_eq!(
+            apply("1\r\n2\r\n3\r\n", &patch, &select(None), false),
+            "1\r\nX\r\n3\r\n"
+        );
+        assert_eq!(
+            apply("1\r\nX\r\n3\r\n", &patch, &select(None), true),
+            "1\r\n2\r\n3\r\n"
+        );
+    }
+
+    #[test]
+    fn handles_a_missing_newline_at_end_of_file() {
+        let patch = patch(
+            (1, 2),
+            (1, 3),
+            vec![
+                line(' ', "1\n"),
+                line('-', "2"),
+                line('>', "\n\\ No newline at end of file\n"),
+                line('+', "2\n"),
+                line('+', "3"),
+                line('<', "\n\\ No newline at end of file\n"),
+            ],
+        );
+
+        assert_eq!(apply("1\n2", &patch, &select(None), false), "1\n2\n3");
+        assert_eq!(
+            apply("1\n2", &patch, &select(Some(vec![1, 3])), false),
+            "1\n2\n"
+        );
+        assert_eq!(
+            apply("1\n2", &patch, &select(Some(vec![4])), false),
+            "1\n2\n3"
+        );
+        assert_eq!(apply("1\n2\n3", &patch, &select(None), true), "1\n2");
+    }
+}
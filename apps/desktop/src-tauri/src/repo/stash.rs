@@ -0,0 +1,282 @@
+use git2::{build::CheckoutBuilder, ErrorCode, Oid, Repository, StashApplyOptions, StashFlags};
+use serde::Serialize;
+use tauri::{command, AppHandle};
+
+use crate::repo::{
+    commit::convert_date,
+    error::{Error, Result},
+    file::{diff_state_from_trees, DiffState},
+    open_repo,
+    status::{conflicted_paths, diff_tree_changes, FileChange},
+};
+
+#[derive(Clone, Serialize)]
+pub struct StashInfo {
+    index: usize,
+    oid: String,
+    message: String,
+    branch: Option<String>,
+    base_oid: String,
+    date: String,
+    has_untracked: bool,
+}
+
+#[derive(Clone, Serialize)]
+pub struct StashApplyResult {
+    pub(crate) status: String,
+    conflicted_paths: Vec<String>,
+    message: Option<String>,
+    dropped: bool,
+}
+
+fn find_stash_oid(repo: &mut Repository, stash_index: usize) -> Result<Oid> {
+    let mut found: Option<Oid> = None;
+    repo.stash_foreach(|index, _message, oid| {
+        if index == stash_index {
+            found = Some(*oid);
+            return false;
+        }
+        true
+    })?;
+
+    found.ok_or_else(|| Error::StashError(format!("Stash @{{{}}} not found", stash_index)))
+}
+
+/// Stash messages look like `On <branch>: <msg>` or `WIP on <branch>: <summary>`.
+fn branch_from_message(message: &str) -> Option<String> {
+    let rest = message
+        .strip_prefix("WIP on ")
+        .or_else(|| message.strip_prefix("On "))?;
+
+    rest.split_once(':').map(|(branch, _)| branch.to_string())
+}
+
+fn apply_stash(
+    repo: &mut Repository,
+    stash_index: usize,
+    reinstate_index: bool,
+) -> Result<StashApplyResult> {
+    let mut checkout_builder = CheckoutBuilder::new();
+    checkout_builder
+        .allow_conflicts(true)
+        .conflict_style_merge(true);
+
+    let mut apply_options = StashApplyOptions::new();
+    apply_options.checkout_options(checkout_builder);
+    if reinstate_index {
+        apply_options.reinstantiate_index();
+    }
+
+    match repo.stash_apply(stash_index, Some(&mut apply_options)) {
+        Ok(()) => {}
+        Err(err) if err.code() == ErrorCode::NotFound => {
+            return Err(Error::StashError(format!(
+                "Stash @{{{}}} not found",
+                stash_index
+            )))
+        }
+        Err(err)
+            if matches!(
+                err.code(),
+                ErrorCode::Conflict | ErrorCode::Uncommitted | ErrorCode::MergeConflict
+            ) =>
+        {
+            return Ok(StashApplyResult {
+                status: "blocked".to_string(),
+                conflicted_paths: Vec::new(),
+                message: Some(err.message().to_string()),
+                dropped: false,
+            });
+        }
+        Err(err) => return Err(err.into()),
+    }
+
+    let mut index = repo.index()?;
+    index.read(true)?;
+    let conflicts = conflicted_paths(&index)?;
+
+    Ok(StashApplyResult {
+        status: if conflicts.is_empty() {
+            "applied".to_string()
+        } else {
+            "conflicts".to_string()
+        },
+        conflicted_paths: conflicts,
+        message: None,
+        dropped: false,
+    })
+}
+
+/// Like `git stash pop`, the entry is kept when the apply needs attention.
+pub(crate) fn pop_stash_entry(
+    repo: &mut Repository,
+    stash_index: usize,
+    reinstate_index: bool,
+) -> Result<StashApplyResult> {
+    let mut result = apply_stash(repo, stash_index, reinstate_index)?;
+    if result.status == "applied" {
+        repo.stash_drop(stash_index)?;
+        result.dropped = true;
+    }
+
+    Ok(result)
+}
+
+#[command]
+pub fn save_stash(
+    app: AppHandle,
+    message: Option<String>,
+    include_untracked: bool,
+    keep_index: bool,
+    repo_path: Option<String>,
+) -> Result<String> {
+    let mut repo = open_repo(app.clone(), repo_path)?;
+    let signature = repo.signature()?;
+
+    let mut flags = StashFlags::DEFAULT;
+    if include_untracked {
+        flags |= StashFlags::INCLUDE_UNTRACKED;
+    }
+    if keep_index {
+        flags |= StashFlags::KEEP_INDEX;
+    }
+
+    let message = message.filter(|value| !value.trim().is_empty());
+    let stash_oid = match repo.stash_save2(&signature, message.as_deref(), Some(flags)) {
+        Ok(oid) => oid,
+        Err(err) if err.code() == ErrorCode::NotFound => {
+            return Err(Error::StashError(
+                "There are no local changes to stash".to_string(),
+            ))
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    Ok(stash_oid.to_string())
+}
+
+#[command]
+pub fn list_stashes(app: AppHandle, repo_path: Option<String>) -> Result<Vec<StashInfo>> {
+    let mut repo = open_repo(app.clone(), repo_path)?;
+
+    let mut entries: Vec<(usize, String, Oid)> = Vec::new();
+    repo.stash_foreach(|index, message, oid| {
+        entries.push((index, message.to_string(), *oid));
+        true
+    })?;
+
+    let mut stashes: Vec<StashInfo> = Vec::new();
+    for (index, message, oid) in entries {
+        let commit = repo.find_commit(oid)?;
+        let base_oid = commit
+            .parent_id(0)
+            .map(|parent| parent.to_string())
+            .unwrap_or_default();
+
+        stashes.push(StashInfo {
+            index,
+            oid: oid.to_string(),
+            branch: branch_from_message(&message),
+            message,
+            base_oid,
+            date: convert_date(commit.time()),
+            has_untracked: commit.parent_count() > 2,
+        });
+    }
+
+    Ok(stashes)
+}
+
+#[command]
+pub fn get_stash_changes(
+    app: AppHandle,
+    stash_index: usize,
+    repo_path: Option<String>,
+) -> Result<Vec<FileChange>> {
+    let mut repo = open_repo(app.clone(), repo_path)?;
+    let stash_oid = find_stash_oid(&mut repo, stash_index)?;
+
+    let stash_commit = repo.find_commit(stash_oid)?;
+    let base_tree = stash_commit.parent(0)?.tree()?;
+    let stash_tree = stash_commit.tree()?;
+
+    let mut changes = diff_tree_changes(&repo, Some(&base_tree), Some(&stash_tree))?;
+
+    if stash_commit.parent_count() > 2 {
+        let untracked_tree = stash_commit.parent(2)?.tree()?;
+        changes.extend(diff_tree_changes(&repo, None, Some(&untracked_tree))?);
+    }
+
+    Ok(changes)
+}
+
+#[command]
+pub fn get_stash_file_diff(
+    app: AppHandle,
+    stash_index: usize,
+    file_path: String,
+    repo_path: Option<String>,
+) -> Result<DiffState> {
+    let mut repo = open_repo(app.clone(), repo_path)?;
+    let stash_oid = find_stash_oid(&mut repo, stash_index)?;
+
+    let stash_commit = repo.find_commit(stash_oid)?;
+    let base_tree = stash_commit.parent(0)?.tree()?;
+    let stash_tree = stash_commit.tree()?;
+
+    if stash_commit.parent_count() > 2 {
+        let untracked_tree = stash_commit.parent(2)?.tree()?;
+        if untracked_tree
+            .get_path(std::path::Path::new(&file_path))
+            .is_ok()
+        {
+            return Ok(diff_state_from_trees(
+                &repo,
+                None,
+                Some(&untracked_tree),
+                &file_path,
+            ));
+        }
+    }
+
+    Ok(diff_state_from_trees(
+        &repo,
+        Some(&base_tree),
+        Some(&stash_tree),
+        &file_path,
+    ))
+}
+
+#[command]
+pub fn apply_stash_entry(
+    app: AppHandle,
+    stash_index: usize,
+    reinstate_index: bool,
+    repo_path: Option<String>,
+) -> Result<StashApplyResult> {
+    let mut repo = open_repo(app.clone(), repo_path)?;
+
+    apply_stash(&mut repo, stash_index, reinstate_index)
+}
+
+#[command]
+pub fn pop_stash(
+    app: AppHandle,
+    stash_index: usize,
+    reinstate_index: bool,
+    repo_path: Option<String>,
+) -> Result<StashApplyResult> {
+    let mut repo = open_repo(app.clone(), repo_path)?;
+
+    pop_stash_entry(&mut repo, stash_index, reinstate_index)
+}
+
+#[command]
+pub fn drop_stash(app: AppHandle, stash_index: usize, repo_path: Option<String>) -> Result<()> {
+    let mut repo = open_repo(app.clone(), repo_path)?;
+
+    find_stash_oid(&mut repo, stash_index)?;
+    repo.stash_drop(stash_index)?;
+
+    Ok(())
+}
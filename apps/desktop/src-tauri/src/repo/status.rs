@@ -2,7 +2,7 @@ use crate::{
     repo::error::{Result},
     repo::open_repo,
 };
-use git2::{Oid, Status, StatusOptions};
+use git2::{DiffDelta, Index, IndexConflict, Oid, Repository, Status, StatusOptions, Tree};
 use serde::Serialize;
 use tauri::{command, AppHandle};
 
@@ -12,6 +12,27 @@ pub struct FileChange {
     status: String,
 }
 
+#[derive(Debug, Serialize)]
+pub struct StatusEntry {
+    path: String,
+    old_path: Option<String>,
+    status: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ConflictEntry {
+    pub(crate) path: String,
+    pub(crate) kind: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepoStatus {
+    staged: Vec<StatusEntry>,
+    unstaged: Vec<StatusEntry>,
+    conflicted: Vec<ConflictEntry>,
+    ignored: Vec<String>,
+}
+
 #[command]
 pub fn get_repo_changes(app: AppHandle, repo_path: Option<String>) -> Result<Vec<FileChange>> {
     let repo = open_repo(app.clone(), repo_path)?;
@@ -48,6 +69,159 @@ pub fn get_repo_changes(app: AppHandle, repo_path: Option<String>) -> Result<Vec
     Ok(changes)
 }
 
+fn delta_paths(delta: Option<DiffDelta>, fallback: &str) -> (String, Option<String>) {
+    let Some(delta) = delta else {
+        return (fallback.to_string(), None);
+    };
+
+    let new_path = delta
+        .new_file()
+        .path()
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|| fallback.to_string());
+    let old_path = delta
+        .old_file()
+        .path()
+        .map(|path| path.to_string_lossy().to_string())
+        .filter(|path| path != &new_path);
+
+    (new_path, old_path)
+}
+
+fn index_status_label(status: Status) -> Option<&'static str> {
+    if status.contains(Status::INDEX_RENAMED) {
+        Some("Renamed")
+    } else if status.contains(Status::INDEX_NEW) {
+        Some("New")
+    } else if status.contains(Status::INDEX_DELETED) {
+        Some("Deleted")
+    } else if status.contains(Status::INDEX_TYPECHANGE) {
+        Some("Typechange")
+    } else if status.contains(Status::INDEX_MODIFIED) {
+        Some("Modified")
+    } else {
+        None
+    }
+}
+
+fn worktree_status_label(status: Status) -> Option<&'static str> {
+    if status.contains(Status::WT_RENAMED) {
+        Some("Renamed")
+    } else if status.contains(Status::WT_NEW) {
+        Some("New")
+    } else if status.contains(Status::WT_DELETED) {
+        Some("Deleted")
+    } else if status.contains(Status::WT_TYPECHANGE) {
+        Some("Typechange")
+    } else if status.contains(Status::WT_MODIFIED) {
+        Some("Modified")
+    } else {
+        None
+    }
+}
+
+fn conflict_kind(conflict: &IndexConflict) -> &'static str {
+    match (
+        conflict.ancestor.is_some(),
+        conflict.our.is_some(),
+        conflict.their.is_some(),
+    ) {
+        (true, true, true) => "both_modified",
+        (false, true, true) => "both_added",
+        (true, false, true) => "deleted_by_us",
+        (true, true, false) => "deleted_by_them",
+        (false, true, false) => "added_by_us",
+        (false, false, true) => "added_by_them",
+        _ => "both_deleted",
+    }
+}
+
+pub(crate) fn conflict_entries(index: &Index) -> Result<Vec<ConflictEntry>> {
+    let mut entries: Vec<ConflictEntry> = Vec::new();
+
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        let entry = conflict
+            .our
+            .as_ref()
+            .or(conflict.their.as_ref())
+            .or(conflict.ancestor.as_ref());
+
+        if let Some(entry) = entry {
+            entries.push(ConflictEntry {
+                path: String::from_utf8_lossy(&entry.path).to_string(),
+                kind: conflict_kind(&conflict).to_string(),
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
+#[command]
+pub fn get_repo_status(
+    app: AppHandle,
+    include_ignored: bool,
+    repo_path: Option<String>,
+) -> Result<RepoStatus> {
+    let repo = open_repo(app.clone(), repo_path)?;
+
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .renames_head_to_index(true)
+        .renames_index_to_workdir(true)
+        .include_ignored(include_ignored)
+        .recurse_ignored_dirs(false);
+
+    let statuses = repo.statuses(Some(&mut opts))?;
+
+    let mut staged: Vec<StatusEntry> = Vec::new();
+    let mut unstaged: Vec<StatusEntry> = Vec::new();
+    let mut ignored: Vec<String> = Vec::new();
+
+    for entry in statuses.iter() {
+        let status = entry.status();
+        let path = entry.path().unwrap_or("");
+
+        if status.contains(Status::CONFLICTED) {
+            continue;
+        }
+
+        if status.contains(Status::IGNORED) {
+            ignored.push(path.to_string());
+            continue;
+        }
+
+        if let Some(label) = index_status_label(status) {
+            let (new_path, old_path) = delta_paths(entry.head_to_index(), path);
+            staged.push(StatusEntry {
+                path: new_path,
+                old_path,
+                status: label.to_string(),
+            });
+        }
+
+        if let Some(label) = worktree_status_label(status) {
+            let (new_path, old_path) = delta_paths(entry.index_to_workdir(), path);
+            unstaged.push(StatusEntry {
+                path: new_path,
+                old_path,
+                status: label.to_string(),
+            });
+        }
+    }
+
+    let conflicted = conflict_entries(&repo.index()?)?;
+
+    Ok(RepoStatus {
+        staged,
+        unstaged,
+        conflicted,
+        ignored,
+    })
+}
+
 #[command]
 pub fn get_repo_changes_from_commit(
     app: AppHandle,
@@ -67,7 +241,15 @@ pub fn get_repo_changes_from_commit(
         None
     };
 
-    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+    diff_tree_changes(&repo, parent_tree.as_ref(), Some(&tree))
+}
+
+pub(crate) fn diff_tree_changes(
+    repo: &Repository,
+    old_tree: Option<&Tree>,
+    new_tree: Option<&Tree>,
+) -> Result<Vec<FileChange>> {
+    let diff = repo.diff_tree_to_tree(old_tree, new_tree, None)?;
     let mut files: Vec<FileChange> = Vec::new();
 
     for delta in diff.deltas() {
@@ -95,3 +277,10 @@ pub fn get_repo_changes_from_commit(
 
     Ok(files)
 }
+
+pub(crate) fn conflicted_paths(index: &Index) -> Result<Vec<String>> {
+    Ok(conflict_entries(index)?
+        .into_iter()
+        .map(|entry| entry.path)
+        .collect())
+}
@@ -41,3 +41,48 @@ export async function deleteMyToken() {
   }
   clearFallbackToken();
 }
+
+export interface Account {
+  host: string;
+  login: string;
+  is_default: boolean;
+}
+
+export async function listAccounts(host?: string | null): Promise<Account[]> {
+  return await invoke("list_accounts", { host });
+}
+
+export async function saveAccount(
+  host: string,
+  login: string,
+  token: string,
+  makeDefault?: boolean
+): Promise<Account[]> {
+  return await invoke("save_account", {
+    host,
+    login,
+    token,
+    makeDefault,
+  });
+}
+
+export async function deleteAccount(
+  host: string,
+  login: string
+): Promise<Account[]> {
+  return await invoke("delete_account", { host, login });
+}
+
+export async function setDefaultAccount(
+  host: string,
+  login: string
+): Promise<Account[]> {
+  return await invoke("set_default_account", { host, login });
+}
+
+export async function getAccountToken(
+  host: string,
+  login?: string | null
+): Promise<string> {
+  return await invoke("get_account_token", { host, login });
+}
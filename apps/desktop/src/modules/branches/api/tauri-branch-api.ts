@@ -9,6 +9,13 @@ export interface BranchType {
   name: string,
   type_of: typeOfBranch | "Local" | "Remote",
   is_head: boolean,
+  upstream: string | null,
+  upstream_gone: boolean,
+  ahead: number | null,
+  behind: number | null,
+  tip_oid: string | null,
+  tip_summary: string | null,
+  tip_date: string | null,
 }
 
 export type MergeAnalysisKind = "up_to_date" | "fast_forward" | "normal_merge";
@@ -25,10 +32,17 @@ export interface FastForwardResult {
   target_oid: string;
 }
 
+export interface MergeConflict {
+  path: string;
+  kind: string;
+}
+
 export interface NormalMergeResult {
   source_branch: string;
   target_branch: string;
-  commit_oid: string;
+  status: "merged" | "conflicts";
+  commit_oid: string | null;
+  conflicts: MergeConflict[];
 }
 
 export async function getBranches(repoPath?: string): Promise<BranchType[]> {
@@ -37,17 +51,34 @@ export async function getBranches(repoPath?: string): Promise<BranchType[]> {
   });
 }
 
-export async function createBranch(branchName: string, repoPath?: string) {
+export async function createBranch(
+  branchName: string,
+  repoPath?: string,
+  startPoint?: string,
+  checkout?: boolean
+) {
   return await invoke("create_branch", {
     repo_path: repoPath,
-    branch_name: branchName
+    branch_name: branchName,
+    startPoint,
+    checkout,
   })
 }
 
-export async function checkoutBranch(branchName: string, repoPath?: string) {
-  return await invoke("checkout_branch", {
+export type CheckoutMode = "carry_over" | "auto_stash" | "abort";
+
+export interface CheckoutResult {
+  status: "checked_out" | "blocked" | "conflicts";
+  target: string;
+  blocking_paths: string[];
+  stash: { status: string; conflicted_paths: string[]; message: string | null; dropped: boolean } | null;
+}
+
+export async function checkoutBranch(branchName: string, repoPath?: string, mode?: CheckoutMode) {
+  return await invoke<CheckoutResult>("checkout_branch", {
     repo_path: repoPath,
-    branch_name: branchName
+    branch_name: branchName,
+    mode,
   })
 }
 
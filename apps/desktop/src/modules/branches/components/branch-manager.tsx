@@ -97,8 +97,14 @@ export function BranchManager({ repoPath, currentBranch, isRepoSelected }: Branc
     checkoutBranchMutation.mutate(
       { branchName, repoPath },
       {
-        onSuccess: () => {
-          toast.success(`Switched to ${branchName}`);
+        onSuccess: (result) => {
+          if (result.status === "blocked") {
+            toast.error(`Local changes would be overwritten: ${result.blocking_paths.join(", ")}`);
+          } else if (result.status === "conflicts") {
+            toast.warning(`Switched to ${branchName}, but reapplying local changes caused conflicts`);
+          } else {
+            toast.success(`Switched to ${branchName}`);
+          }
         },
         onError: (error) => {
           const message = error instanceof Error ? error.message : "Unable to switch branch";
@@ -192,7 +198,13 @@ export function BranchManager({ repoPath, currentBranch, isRepoSelected }: Branc
       },
       {
         onSuccess: (result) => {
-          toast.success(`Merged ${result.source_branch} into ${result.target_branch}`);
+          if (result.status === "conflicts") {
+            toast.warning(
+              `Merging ${result.source_branch} into ${result.target_branch} has ${result.conflicts.length} conflicted file(s)`
+            );
+          } else {
+            toast.success(`Merged ${result.source_branch} into ${result.target_branch}`);
+          }
           setMergeAnalysis(null);
         },
         onError: (error) => {
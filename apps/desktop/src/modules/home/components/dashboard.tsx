@@ -1,5 +1,7 @@
 import { cloneRepo } from "@/modules/repo/api/tauri-repo-api";
+import { SshPromptDialog } from "@/modules/repo/components/ssh-prompt-dialog";
 import { StartWithoutRepo } from "@/modules/repo/components/start-without-repo";
+import { useSshPrompts } from "@/modules/repo/hooks/use-ssh-prompts";
 import { useCheckGitFolder } from "@/modules/repo/hooks/use-tauri-repo";
 import { WorkspaceShell } from "@/modules/workspace/components/workspace-shell";
 import { useAuthStore } from "@/store/github-client";
@@ -8,7 +10,8 @@ import { toast } from "sonner";
 
 export function Dashboard() {
   const { mutate: checkGitRepo } = useCheckGitFolder();
-  const { setLastOpenedRepo, last_opened_repo } = useAuthStore();
+  const { setLastOpenedRepo, last_opened_repo, token } = useAuthStore();
+  const sshPrompts = useSshPrompts();
 
   const handleCheckGitRepoLocal = async () => {
     const folder = await openFolderSelector();
@@ -27,14 +30,32 @@ export function Dashboard() {
     });
   }
 
-  const handleCloneGitRepo = async (payload?: { url?: string; destination?: string }) => {
+  const handleCloneGitRepo = async (payload?: {
+    url?: string;
+    destination?: string;
+    recurseSubmodules?: boolean;
+    operationId?: string;
+  }) => {
     if (!payload?.url || !payload?.destination) {
       toast.error("Please provide a repo URL and destination folder.");
       return;
     }
 
     try {
-      await cloneRepo(payload.url, payload.destination);
+      const result = await cloneRepo(
+        payload.url,
+        payload.destination,
+        token,
+        { recurse_submodules: payload.recurseSubmodules ?? false },
+        payload.operationId
+      );
+      if (result.status === "cancelled") {
+        toast.info("Clone cancelled.");
+        return;
+      }
+      if (result.submodule_error) {
+        toast.warning(`Cloned, but some submodules weren't updated: ${result.submodule_error}`);
+      }
       checkGitRepo(payload.destination, {
         onSuccess: (data) => {
           if (data) {
@@ -49,6 +70,9 @@ export function Dashboard() {
         },
       });
     } catch (_error) {
+      if (await sshPrompts.promptAfterFailure(() => handleCloneGitRepo(payload))) {
+        return;
+      }
       const message =
         _error instanceof Error
           ? _error.message
@@ -61,7 +85,10 @@ export function Dashboard() {
 
   return (
     !last_opened_repo ? (
-      <StartWithoutRepo cloneGitRepo={handleCloneGitRepo} openLocal={handleCheckGitRepoLocal} />
+      <>
+        <StartWithoutRepo cloneGitRepo={handleCloneGitRepo} openLocal={handleCheckGitRepoLocal} />
+        <SshPromptDialog prompts={sshPrompts.prompts} onClose={sshPrompts.close} onResolved={sshPrompts.resolve} />
+      </>
     ) : (
       <WorkspaceShell repoPath={last_opened_repo} />
     )
@@ -1,9 +1,68 @@
 import { invoke } from "@tauri-apps/api/core";
 
+/** How a network operation authenticated. */
+export type CredentialSource =
+  | "token"
+  | "credential_helper"
+  | "account"
+  | "ssh_agent"
+  | "ssh_key";
+
+export interface PushRejection {
+  reference: string;
+  message: string;
+}
+
 export interface PushRepoResult {
+  status: "pushed" | "rejected" | "stale_lease" | "cancelled";
   branch_name: string;
   remote_name: string;
+  remote_branch: string;
   set_upstream: boolean;
+  forced: boolean;
+  rejected: PushRejection[];
+  credential_method: CredentialSource | null;
+}
+
+export interface PushRepoOptions {
+  branch_name?: string;
+  remote_name?: string;
+  remote_branch?: string;
+  force_with_lease?: boolean;
+  push_tags?: boolean;
+}
+
+export interface FetchedRef {
+  name: string;
+  old_oid: string | null;
+  new_oid: string | null;
+}
+
+export interface FetchSummary {
+  status: "fetched" | "cancelled";
+  remote_name: string;
+  created: FetchedRef[];
+  updated: FetchedRef[];
+  deleted: FetchedRef[];
+  credential_method: CredentialSource | null;
+}
+
+export interface FetchRepoOptions {
+  prune?: boolean;
+  tags?: "auto" | "all" | "none";
+  depth?: number;
+}
+
+export interface FetchProgressPayload {
+  operation_id: string | null;
+  remote_name: string;
+  phase: string;
+  value: number;
+  received_objects: number;
+  total_objects: number;
+  indexed_deltas: number;
+  total_deltas: number;
+  received_bytes: number;
 }
 
 export interface RepoState {
@@ -31,20 +90,198 @@ export async function removeRepoFromView(repoPath: string): Promise<RepoState> {
   return await invoke("remove_repo_from_view", { repo_path: repoPath });
 }
 
-export async function cloneRepo(url: string, filePath: string): Promise<void> {
-  await invoke("clone_repo", { url, filePath });
+export interface CloneResult {
+  status: "cloned" | "cancelled";
+  path: string;
+  operation_id: string;
+  bare: boolean;
+  submodules: string[];
+  credential_method: CredentialSource | null;
+  submodule_error: string | null;
+}
+
+export interface CloneRepoOptions {
+  branch?: string;
+  depth?: number;
+  recurse_submodules?: boolean;
+  bare?: boolean;
+  mirror?: boolean;
 }
 
-export async function fetchRepo(repoPath?: string | null, token?: string | null): Promise<void> {
-  await invoke("fetch_repo", {
+export interface CloneProgressPayload {
+  operation_id: string;
+  phase: "Receiving objects" | "Resolving deltas" | "Checking out files" | "Updating submodules";
+  value: number;
+  current: number;
+  total: number;
+}
+
+export interface OperationInfo {
+  operation_id: string;
+  kind: "clone" | "fetch" | "push" | "pull";
+}
+
+export async function cloneRepo(
+  url: string,
+  filePath: string,
+  token?: string | null,
+  options?: CloneRepoOptions,
+  operationId?: string
+): Promise<CloneResult> {
+  return await invoke("clone_repo", { url, filePath, token, options, operationId });
+}
+
+export async function cancelOperation(operationId: string): Promise<boolean> {
+  return await invoke("cancel_operation", { operationId });
+}
+
+export async function listOperations(): Promise<OperationInfo[]> {
+  return await invoke("list_operations");
+}
+
+export async function fetchRepo(
+  repoPath?: string | null,
+  token?: string | null,
+  remoteName?: string | null,
+  options?: FetchRepoOptions,
+  operationId?: string
+): Promise<FetchSummary> {
+  return await invoke("fetch_repo", {
     repo_path: repoPath,
     token,
+    remoteName,
+    options,
+    operationId,
   });
 }
 
-export async function pushRepo(repoPath?: string | null, token?: string | null): Promise<PushRepoResult> {
+export async function pushRepo(
+  repoPath?: string | null,
+  token?: string | null,
+  options?: PushRepoOptions,
+  operationId?: string
+): Promise<PushRepoResult> {
   return await invoke("push_repo", {
     repo_path: repoPath,
     token,
+    options,
+    operationId,
   });
 }
+
+export interface AutoFetchSettings {
+  enabled: boolean;
+  interval_secs: number;
+  all_repos: boolean;
+  pause_on_battery: boolean;
+}
+
+export interface AutoFetchStatusPayload {
+  repo_path: string | null;
+  status: "fetched" | "failed" | "offline" | "skipped" | "paused_on_battery";
+  error: string | null;
+  next_fetch_in_secs: number;
+}
+
+export interface UpstreamCommitsPayload {
+  repo_path: string;
+  branch_name: string;
+  upstream: string;
+  new_commits: number;
+  behind: number;
+}
+
+export async function getAutoFetchSettings(): Promise<AutoFetchSettings> {
+  return await invoke("get_auto_fetch_settings");
+}
+
+export async function setAutoFetchSettings(
+  settings: AutoFetchSettings
+): Promise<AutoFetchSettings> {
+  return await invoke("set_auto_fetch_settings", { settings });
+}
+
+export async function triggerAutoFetch(): Promise<void> {
+  await invoke("trigger_auto_fetch");
+}
+
+export type CredentialMethod = "auto" | "token" | "ssh_agent" | "ssh_key";
+
+export interface RemoteCredentialSettings {
+  remote_name: string;
+  method: CredentialMethod;
+  ssh_key_file: string | null;
+}
+
+export interface SshKeyInfo {
+  path: string;
+  encrypted: boolean;
+  has_passphrase: boolean;
+}
+
+export interface UnknownHost {
+  host: string;
+  key_type: string;
+  fingerprint: string;
+  changed: boolean;
+}
+
+export interface SshPrompts {
+  passphrases: string[];
+  hosts: UnknownHost[];
+}
+
+export async function getRemoteCredentialMethod(
+  remoteName: string,
+  repoPath?: string | null
+): Promise<RemoteCredentialSettings> {
+  return await invoke("get_remote_credential_method", {
+    repo_path: repoPath,
+    remoteName,
+  });
+}
+
+export async function setRemoteCredentialMethod(
+  remoteName: string,
+  method: CredentialMethod,
+  sshKeyFile?: string | null,
+  repoPath?: string | null
+): Promise<RemoteCredentialSettings> {
+  return await invoke("set_remote_credential_method", {
+    repo_path: repoPath,
+    remoteName,
+    method,
+    sshKeyFile,
+  });
+}
+
+export async function listSshKeys(host?: string | null): Promise<SshKeyInfo[]> {
+  return await invoke("list_ssh_keys", { host });
+}
+
+export async function getSshPrompts(): Promise<SshPrompts> {
+  return await invoke("get_ssh_prompts");
+}
+
+export async function setSshKeyPassphrase(
+  keyPath: string,
+  passphrase: string,
+  remember: boolean
+): Promise<void> {
+  await invoke("set_ssh_key_passphrase", {
+    keyPath,
+    passphrase,
+    remember,
+  });
+}
+
+export async function forgetSshKeyPassphrase(keyPath: string): Promise<void> {
+  await invoke("forget_ssh_key_passphrase", { keyPath });
+}
+
+export async function trustSshHost(
+  host: string,
+  replaceExisting: boolean
+): Promise<void> {
+  await invoke("trust_ssh_host", { host, replaceExisting });
+}
@@ -1,6 +1,7 @@
 import { Badge } from "@/components/ui/badge";
 import { Button } from "@/components/ui/button";
 import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/card";
+import { Checkbox } from "@/components/ui/checkbox";
 import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
 import { Progress } from "@/components/ui/progress";
@@ -17,6 +18,9 @@ export function ClonePanel({
   isCloning,
   progressPhase,
   progressValue,
+  recurseSubmodules,
+  onRecurseSubmodulesChange,
+  onCancel,
 }: {
   selectedRepo: GithubRepo | null;
   repoUrl: string;
@@ -28,6 +32,9 @@ export function ClonePanel({
   isCloning: boolean;
   progressPhase: string;
   progressValue: number;
+  recurseSubmodules: boolean;
+  onRecurseSubmodulesChange: (value: boolean) => void;
+  onCancel: () => void;
 }) {
   const helperText = selectedRepo
     ? `Cloning ${selectedRepo.full_name}`
@@ -93,6 +100,15 @@ export function ClonePanel({
           </div>
         </div>
 
+        <label className="flex cursor-pointer items-center justify-between rounded-md border border-border/60 bg-muted/20 px-3 py-2 text-xs text-muted-foreground">
+          <span>Clone submodules</span>
+          <Checkbox
+            checked={recurseSubmodules}
+            disabled={isCloning}
+            onCheckedChange={(value) => onRecurseSubmodulesChange(value === true)}
+          />
+        </label>
+
         {isCloning || progressValue > 0 ? (
           <div className="rounded-lg border border-border/60 bg-muted/20 px-4 py-4">
             <div className="flex items-center justify-between text-xs uppercase tracking-[0.18em] text-muted-foreground">
@@ -103,10 +119,15 @@ export function ClonePanel({
           </div>
         ) : null}
 
-        <div className="mt-auto">
-          <Button onClick={onClone} disabled={!canClone} className="h-11 w-full">
+        <div className="mt-auto flex gap-2">
+          <Button onClick={onClone} disabled={!canClone} className="h-11 flex-1">
             {isCloning ? "Cloning..." : "Clone remote repo"}
           </Button>
+          {isCloning ? (
+            <Button variant="outline" onClick={onCancel} className="h-11">
+              Cancel
+            </Button>
+          ) : null}
         </div>
       </CardContent>
     </Card>
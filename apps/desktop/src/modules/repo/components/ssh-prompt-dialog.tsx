@@ -0,0 +1,125 @@
+import { useEffect, useState } from "react";
+import { KeyRound, Loader2, ShieldAlert } from "lucide-react";
+import { toast } from "sonner";
+
+import { Button } from "@/components/ui/button";
+import { Card, CardContent, CardFooter, CardHeader, CardTitle } from "@/components/ui/card";
+import { Checkbox } from "@/components/ui/checkbox";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { setSshKeyPassphrase, trustSshHost, type SshPrompts } from "../api/tauri-repo-api";
+
+interface SshPromptDialogProps {
+  prompts: SshPrompts | null;
+  onClose: () => void;
+  onResolved: () => void;
+}
+
+export function SshPromptDialog({ prompts, onClose, onResolved }: SshPromptDialogProps) {
+  const [passphrases, setPassphrases] = useState<Record<string, string>>({});
+  const [remember, setRemember] = useState(false);
+  const [isSaving, setIsSaving] = useState(false);
+
+  useEffect(() => {
+    setPassphrases({});
+    setRemember(false);
+  }, [prompts]);
+
+  if (!prompts) {
+    return null;
+  }
+
+  const hasChangedHost = prompts.hosts.some((host) => host.changed);
+  const canContinue = prompts.passphrases.every((keyPath) => !!passphrases[keyPath]);
+
+  const handleContinue = async () => {
+    setIsSaving(true);
+    try {
+      for (const host of prompts.hosts) {
+        await trustSshHost(host.host, host.changed);
+      }
+      for (const keyPath of prompts.passphrases) {
+        await setSshKeyPassphrase(keyPath, passphrases[keyPath], remember);
+      }
+      onResolved();
+    } catch (error) {
+      toast.error(error instanceof Error ? error.message : String(error));
+    } finally {
+      setIsSaving(false);
+    }
+  };
+
+  return (
+    <div className="fixed inset-0 z-50 flex items-center justify-center bg-background/80 backdrop-blur-sm">
+      <Card className="w-[460px]">
+        <CardHeader className="space-y-1">
+          <CardTitle className="text-base">SSH needs your confirmation</CardTitle>
+          <p className="text-sm text-muted-foreground">The operation will be retried once you continue.</p>
+        </CardHeader>
+
+        <CardContent className="space-y-4 text-sm">
+          {prompts.hosts.map((host) => (
+            <div
+              key={host.host}
+              className={`space-y-1 rounded-md border px-3 py-2 ${
+                host.changed ? "border-destructive/60 bg-destructive/10" : "border-border/60 bg-muted/30"
+              }`}
+            >
+              <div className="flex items-center gap-2 font-medium text-foreground">
+                <ShieldAlert className="h-4 w-4" />
+                <span>{host.host}</span>
+              </div>
+              <p className="text-xs text-muted-foreground">
+                {host.changed
+                  ? "The host key differs from the one in known_hosts. This can mean someone is intercepting the connection. Only replace it if you know the server's key changed."
+                  : "This host isn't in known_hosts yet. Check the fingerprint before trusting it."}
+              </p>
+              <p className="break-all font-mono text-xs text-foreground">
+                {host.key_type} {host.fingerprint}
+              </p>
+            </div>
+          ))}
+
+          {prompts.passphrases.map((keyPath) => (
+            <div key={keyPath} className="space-y-1.5">
+              <Label htmlFor={`passphrase-${keyPath}`} className="flex items-center gap-2 text-xs">
+                <KeyRound className="h-3.5 w-3.5" />
+                <span className="truncate">Passphrase for {keyPath}</span>
+              </Label>
+              <Input
+                id={`passphrase-${keyPath}`}
+                type="password"
+                value={passphrases[keyPath] ?? ""}
+                onChange={(event) =>
+                  setPassphrases((current) => ({ ...current, [keyPath]: event.target.value }))
+                }
+              />
+            </div>
+          ))}
+
+          {prompts.passphrases.length > 0 ? (
+            <label className="flex cursor-pointer items-center justify-between rounded-md border border-border/60 bg-muted/20 px-3 py-2 text-xs text-muted-foreground">
+              <span>Remember in the system keychain</span>
+              <Checkbox checked={remember} onCheckedChange={(value) => setRemember(value === true)} />
+            </label>
+          ) : null}
+        </CardContent>
+
+        <CardFooter className="flex justify-end gap-2">
+          <Button type="button" variant="outline" disabled={isSaving} onClick={onClose}>
+            Cancel
+          </Button>
+          <Button
+            type="button"
+            variant={hasChangedHost ? "destructive" : "default"}
+            disabled={!canContinue || isSaving}
+            onClick={handleContinue}
+          >
+            {isSaving ? <Loader2 className="mr-2 h-3.5 w-3.5 animate-spin" /> : null}
+            {hasChangedHost ? "Replace key and retry" : "Continue"}
+          </Button>
+        </CardFooter>
+      </Card>
+    </div>
+  );
+}
@@ -1,6 +1,7 @@
 import { type GithubRepo } from "@/modules/repo/api/github-repo-api";
 import { useGetGithubRepos } from "@/modules/repo/hooks/use-github-repo";
 import { openFolderSelector } from "@/utils/open-folder";
+import { cancelOperation } from "@/modules/repo/api/tauri-repo-api";
 import { listen } from "@tauri-apps/api/event";
 import { useEffect, useMemo, useState } from "react";
 import { Badge } from "@/components/ui/badge";
@@ -17,6 +18,8 @@ interface StartWithoutRepoProps {
     repo?: GithubRepo | null;
     url?: string;
     destination?: string;
+    recurseSubmodules?: boolean;
+    operationId?: string;
   }) => Promise<void> | void;
 }
 
@@ -29,6 +32,8 @@ export function StartWithoutRepo({ openLocal, cloneGitRepo }: StartWithoutRepoPr
   const [progressValue, setProgressValue] = useState(0);
   const [progressPhase, setProgressPhase] = useState("");
   const [isCloning, setIsCloning] = useState(false);
+  const [recurseSubmodules, setRecurseSubmodules] = useState(false);
+  const [cloneOperationId, setCloneOperationId] = useState<string | null>(null);
 
   const handleSelectRepo = (repo: GithubRepo) => {
     setSelectedRepo(repo);
@@ -39,16 +44,27 @@ export function StartWithoutRepo({ openLocal, cloneGitRepo }: StartWithoutRepoPr
     setIsCloning(true);
     setProgressValue(0);
     setProgressPhase("Preparing clone");
+    const operationId = `clone-${crypto.randomUUID()}`;
+    setCloneOperationId(operationId);
     try {
       await cloneGitRepo({
         repo: selectedRepo,
         url: repoUrl.trim(),
         destination: cloneDestination,
+        recurseSubmodules,
+        operationId,
       });
       setProgressValue(100);
       setProgressPhase("Downloaded");
     } finally {
       setIsCloning(false);
+      setCloneOperationId(null);
+    }
+  };
+
+  const handleCancelClone = async () => {
+    if (cloneOperationId) {
+      await cancelOperation(cloneOperationId);
     }
   };
 
@@ -142,6 +158,9 @@ export function StartWithoutRepo({ openLocal, cloneGitRepo }: StartWithoutRepoPr
                 isCloning={isCloning}
                 progressPhase={progressPhase}
                 progressValue={progressValue}
+                recurseSubmodules={recurseSubmodules}
+                onRecurseSubmodulesChange={setRecurseSubmodules}
+                onCancel={handleCancelClone}
               />
             </div>
             <div className="mt-6 grid gap-6 lg:grid-cols-2">
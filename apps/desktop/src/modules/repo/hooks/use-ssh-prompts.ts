@@ -0,0 +1,36 @@
+import { useRef, useState } from "react";
+import { getSshPrompts, type SshPrompts } from "../api/tauri-repo-api";
+
+export function useSshPrompts() {
+  const [prompts, setPrompts] = useState<SshPrompts | null>(null);
+  const retryRef = useRef<(() => void) | null>(null);
+
+  // Resolves to true when the failed operation is waiting on the user, in
+  // which case the dialog takes over and retries it once answered.
+  const promptAfterFailure = async (retry: () => void) => {
+    try {
+      const pending = await getSshPrompts();
+      if (pending.hosts.length === 0 && pending.passphrases.length === 0) {
+        return false;
+      }
+      retryRef.current = retry;
+      setPrompts(pending);
+      return true;
+    } catch {
+      return false;
+    }
+  };
+
+  const close = () => {
+    retryRef.current = null;
+    setPrompts(null);
+  };
+
+  const resolve = () => {
+    const retry = retryRef.current;
+    close();
+    retry?.();
+  };
+
+  return { prompts, promptAfterFailure, close, resolve };
+}
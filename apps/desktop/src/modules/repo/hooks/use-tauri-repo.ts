@@ -14,8 +14,12 @@ export function useFetchRepo() {
   const queryClient = useQueryClient();
 
   return useMutation({
-    mutationFn: async (payload: { repoPath?: string | null; token?: string | null }) => {
-      return await fetchRepo(payload.repoPath, payload.token);
+    mutationFn: async (payload: {
+      repoPath?: string | null;
+      token?: string | null;
+      operationId?: string;
+    }) => {
+      return await fetchRepo(payload.repoPath, payload.token, null, undefined, payload.operationId);
     },
     onSuccess: async (_data, variables) => {
       await Promise.all([
@@ -32,8 +36,12 @@ export function usePushRepo() {
   const queryClient = useQueryClient();
 
   return useMutation({
-    mutationFn: async (payload: { repoPath?: string | null; token?: string | null }) => {
-      return await pushRepo(payload.repoPath, payload.token);
+    mutationFn: async (payload: {
+      repoPath?: string | null;
+      token?: string | null;
+      operationId?: string;
+    }) => {
+      return await pushRepo(payload.repoPath, payload.token, undefined, payload.operationId);
     },
     onSuccess: async () => {
       await Promise.all([